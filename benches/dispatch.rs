@@ -0,0 +1,96 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use stuff::{strategies::NanBoxStrategy, Backend, StuffedPtr, StuffingStrategy, Unstuffed};
+
+// stores a `u16` shifted by one with the lowest bit set
+struct LowBit;
+
+macro_rules! impl_low_bit {
+    ($backend:ty) => {
+        impl StuffingStrategy<$backend> for LowBit {
+            type Other = u16;
+
+            fn stuff_other(inner: Self::Other) -> $backend {
+                (<$backend>::from(inner) << 1) | 1
+            }
+
+            fn extract(data: $backend) -> Unstuffed<usize, Self::Other> {
+                match data & 1 == 1 {
+                    true => Unstuffed::Other((data >> 1) as u16),
+                    false => Unstuffed::Ptr(data as usize),
+                }
+            }
+
+            fn stuff_ptr(addr: usize) -> $backend {
+                addr as $backend
+            }
+        }
+    };
+}
+
+impl_low_bit!(usize);
+impl_low_bit!(u64);
+impl_low_bit!(u128);
+
+// every third value is a pointer
+fn mixed<S, B>(ptr: *mut u64, other: impl Fn(usize) -> S::Other) -> Vec<StuffedPtr<u64, S, B>>
+where
+    S: StuffingStrategy<B>,
+    B: Backend,
+{
+    (0..1000)
+        .map(|i| match i % 3 {
+            0 => StuffedPtr::new_ptr(ptr),
+            _ => StuffedPtr::new_other(other(i)),
+        })
+        .collect()
+}
+
+fn bench_backend<S, B>(
+    c: &mut Criterion,
+    name: &str,
+    other: impl Fn(usize) -> S::Other + Copy,
+    sum: impl Fn(S::Other) -> u64 + Copy,
+) where
+    S: StuffingStrategy<B>,
+    B: Backend,
+{
+    let mut value = 1_u64;
+    let ptr = &mut value as *mut u64;
+
+    let mut group = c.benchmark_group(name);
+
+    group.bench_function("new_ptr", |b| {
+        b.iter(|| StuffedPtr::<u64, S, B>::new_ptr(black_box(ptr)))
+    });
+    group.bench_function("new_other", |b| {
+        b.iter(|| StuffedPtr::<u64, S, B>::new_other(black_box(other(1))))
+    });
+
+    let values = mixed::<S, B>(ptr, other);
+    group.bench_with_input(
+        BenchmarkId::new("extract", values.len()),
+        &values,
+        |b, values| {
+            b.iter(|| {
+                values
+                    .iter()
+                    .fold(0_u64, |acc, value| match value.unstuff() {
+                        Unstuffed::Ptr(ptr) => acc.wrapping_add(unsafe { *ptr }),
+                        Unstuffed::Other(other) => acc.wrapping_add(sum(other)),
+                    })
+            })
+        },
+    );
+
+    group.finish();
+}
+
+fn dispatch(c: &mut Criterion) {
+    bench_backend::<LowBit, usize>(c, "usize", |i| i as u16, u64::from);
+    bench_backend::<LowBit, u64>(c, "u64", |i| i as u16, u64::from);
+    bench_backend::<LowBit, u128>(c, "u128", |i| i as u16, u64::from);
+    bench_backend::<NanBoxStrategy, u64>(c, "nan_box", |i| i as f64, |f| f as u64);
+}
+
+criterion_group!(benches, dispatch);
+criterion_main!(benches);
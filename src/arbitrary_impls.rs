@@ -0,0 +1,73 @@
+use core::mem;
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::{Backend, StuffedPtr, StuffingStrategy};
+
+/// Generates either arbitrary `other` data or a pointer, to fuzz the round trip of a
+/// [`StuffingStrategy`].
+///
+/// **The generated pointers must never be dereferenced.** They don't point to any allocation and
+/// don't have any provenance. Their address is aligned for `T` and fits into 32 bits, so that
+/// strategies that use the upper bits of the address can store them.
+///
+/// Only available with the `arbitrary` feature.
+impl<'a, T, S, B> Arbitrary<'a> for StuffedPtr<T, S, B>
+where
+    S: StuffingStrategy<B>,
+    S::Other: Arbitrary<'a>,
+    B: Backend,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        if u.arbitrary()? {
+            let addr = u32::arbitrary(u)? as usize & !(mem::align_of::<T>() - 1);
+            Ok(StuffedPtr::new_ptr(sptr::invalid_mut(addr)))
+        } else {
+            S::Other::arbitrary(u).map(StuffedPtr::new_other)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::mem;
+    use std::vec::Vec;
+
+    use arbitrary::{Arbitrary, Unstructured};
+    use sptr::Strict;
+
+    use crate::{strategy::test_strategies::LowBitU16, StuffedPtr};
+
+    #[test]
+    fn round_trip() {
+        // a simple xorshift to generate the input bytes
+        let mut state = 0x2545_f491_u32;
+        let bytes: Vec<u8> = (0..4096)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect();
+
+        let mut u = Unstructured::new(&bytes);
+        // draws the same values as the `Arbitrary` impl from the same bytes
+        let mut expected = Unstructured::new(&bytes);
+        while !u.is_empty() {
+            let stuffed = StuffedPtr::<u64, LowBitU16>::arbitrary(&mut u).unwrap();
+            if bool::arbitrary(&mut expected).unwrap() {
+                let addr =
+                    u32::arbitrary(&mut expected).unwrap() as usize & !(mem::align_of::<u64>() - 1);
+                let ptr: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(sptr::invalid_mut(addr));
+                assert_eq!(ptr.ptr().map(Strict::addr), Some(addr));
+                assert_eq!(stuffed.ptr().map(Strict::addr), Some(addr));
+            } else {
+                let other = u16::arbitrary(&mut expected).unwrap();
+                let restuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(other);
+                assert_eq!(restuffed.other(), Some(other));
+                assert_eq!(stuffed.other(), Some(other));
+            }
+        }
+    }
+}
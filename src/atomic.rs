@@ -0,0 +1,393 @@
+// loom replaces the atomics with its own types to model check them
+#[cfg(all(not(loom), target_has_atomic = "64"))]
+use core::sync::atomic::AtomicU64;
+#[cfg(not(loom))]
+use core::sync::atomic::AtomicUsize;
+use core::{marker::PhantomData, sync::atomic::Ordering};
+
+#[cfg(all(loom, target_has_atomic = "64"))]
+use loom::sync::atomic::AtomicU64;
+#[cfg(loom)]
+use loom::sync::atomic::AtomicUsize;
+use sptr::Strict;
+
+use crate::{Backend, StuffedPtr, StuffingStrategy, Unstuffed};
+
+/// A [`Backend`] that has an atomic integer type of the same size.
+///
+/// # Safety
+/// `Atomic` must behave like an atomic version of `Self`, storing and returning the exact same
+/// integers.
+pub unsafe trait AtomicBackend: Backend + Copy + PartialEq {
+    /// The atomic integer type, for example `AtomicUsize` for `usize`.
+    type Atomic;
+
+    /// Create a new atomic integer.
+    fn new_atomic(value: Self) -> Self::Atomic;
+
+    /// Load the integer.
+    fn load(atomic: &Self::Atomic, order: Ordering) -> Self;
+
+    /// Store the integer.
+    fn store(atomic: &Self::Atomic, value: Self, order: Ordering);
+
+    /// Swap the integer, returning the previous one.
+    fn swap(atomic: &Self::Atomic, value: Self, order: Ordering) -> Self;
+
+    /// Store `new` if the current integer is `current`, returning the previous integer.
+    fn compare_exchange(
+        atomic: &Self::Atomic,
+        current: Self,
+        new: Self,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<Self, Self>;
+}
+
+macro_rules! impl_atomic_backend {
+    ($ty:ty, $atomic:ty) => {
+        // SAFETY: We just forward to the atomic type
+        unsafe impl AtomicBackend for $ty {
+            type Atomic = $atomic;
+
+            fn new_atomic(value: Self) -> Self::Atomic {
+                <$atomic>::new(value)
+            }
+
+            fn load(atomic: &Self::Atomic, order: Ordering) -> Self {
+                atomic.load(order)
+            }
+
+            fn store(atomic: &Self::Atomic, value: Self, order: Ordering) {
+                atomic.store(value, order)
+            }
+
+            fn swap(atomic: &Self::Atomic, value: Self, order: Ordering) -> Self {
+                atomic.swap(value, order)
+            }
+
+            fn compare_exchange(
+                atomic: &Self::Atomic,
+                current: Self,
+                new: Self,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<Self, Self> {
+                atomic.compare_exchange(current, new, success, failure)
+            }
+        }
+    };
+}
+
+impl_atomic_backend!(usize, AtomicUsize);
+
+#[cfg(target_has_atomic = "64")]
+impl_atomic_backend!(u64, AtomicU64);
+
+/// An atomic version of [`StuffedPtr`], backed by the atomic integer type of the backend.
+///
+/// Only available with the `atomic` feature, which requires Rust 1.60.
+///
+/// # Provenance
+/// Atomic integers can't carry provenance, so the provenance of stored pointers is *exposed*
+/// (see [`sptr::Strict::expose_addr`]) and recovered with [`sptr::from_exposed_addr_mut`] when
+/// a value is loaded. This means that pointers loaded from an `AtomicStuffedPtr` are only
+/// usable if the memory model of the platform supports exposed provenance, and that tools like
+/// Miri may not be able to check them as strictly as pointers from a [`StuffedPtr`].
+pub struct AtomicStuffedPtr<T, S, B = usize>(B::Atomic, PhantomData<StuffedPtr<T, S, B>>)
+where
+    B: AtomicBackend;
+
+impl<T, S, B> AtomicStuffedPtr<T, S, B>
+where
+    S: StuffingStrategy<B>,
+    B: AtomicBackend,
+{
+    /// Create a new `AtomicStuffedPtr`
+    pub fn new(value: StuffedPtr<T, S, B>) -> Self {
+        AtomicStuffedPtr(B::new_atomic(Self::expose(value)), PhantomData)
+    }
+
+    /// Load the value
+    pub fn load(&self, order: Ordering) -> StuffedPtr<T, S, B> {
+        Self::from_exposed(B::load(&self.0, order))
+    }
+
+    /// Store a new value
+    pub fn store(&self, value: StuffedPtr<T, S, B>, order: Ordering) {
+        B::store(&self.0, Self::expose(value), order);
+    }
+
+    /// Store a new value, returning the previous value
+    pub fn swap(&self, value: StuffedPtr<T, S, B>, order: Ordering) -> StuffedPtr<T, S, B> {
+        Self::from_exposed(B::swap(&self.0, Self::expose(value), order))
+    }
+
+    /// Store `new` if the current value is `current`, returning the previous value. On success,
+    /// the previous value is equal to `current`.
+    ///
+    /// Like [`StuffedPtr::compare_exchange`], the comparison is done on the stuffed bits.
+    pub fn compare_exchange(
+        &self,
+        current: StuffedPtr<T, S, B>,
+        new: StuffedPtr<T, S, B>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<StuffedPtr<T, S, B>, StuffedPtr<T, S, B>> {
+        B::compare_exchange(
+            &self.0,
+            current.stuffed_bits(),
+            Self::expose(new),
+            success,
+            failure,
+        )
+        .map(Self::from_exposed)
+        .map_err(Self::from_exposed)
+    }
+
+    /// Fetch the value and apply `f` to it, storing the new value if `f` returns `Some`. Returns
+    /// `Ok` with the previous value if a new value was stored, and `Err` with the current value if
+    /// `f` returned `None`.
+    ///
+    /// Like [`AtomicUsize::fetch_update`], `f` may be called several times if the value was
+    /// changed by another thread in the meantime. The values passed to `f` are loaded like with
+    /// [`AtomicStuffedPtr::load`], so their pointers use the exposed provenance (see the type
+    /// level documentation).
+    ///
+    /// ```
+    /// use core::sync::atomic::Ordering;
+    ///
+    /// use stuff::{strategies::SmallIntStrategy, AtomicStuffedPtr, StuffedPtr};
+    ///
+    /// let counter: AtomicStuffedPtr<(), SmallIntStrategy, u64> =
+    ///     AtomicStuffedPtr::new(StuffedPtr::new_other(1));
+    ///
+    /// let prev = counter.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |value| {
+    ///     value.other().map(|int| StuffedPtr::new_other(int + 1))
+    /// });
+    /// assert_eq!(prev.map(|prev| prev.other()), Ok(Some(1)));
+    /// assert_eq!(counter.load(Ordering::SeqCst).other(), Some(2));
+    /// ```
+    pub fn fetch_update(
+        &self,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        mut f: impl FnMut(StuffedPtr<T, S, B>) -> Option<StuffedPtr<T, S, B>>,
+    ) -> Result<StuffedPtr<T, S, B>, StuffedPtr<T, S, B>> {
+        let mut prev = self.load(fetch_order);
+        while let Some(next) = f(prev) {
+            match self.compare_exchange(prev, next, set_order, fetch_order) {
+                Ok(prev) => return Ok(prev),
+                Err(next_prev) => prev = next_prev,
+            }
+        }
+        Err(prev)
+    }
+
+    fn expose(value: StuffedPtr<T, S, B>) -> B {
+        if let Some(ptr) = value.ptr() {
+            let _ = Strict::expose_addr(ptr);
+        }
+        value.stuffed_bits()
+    }
+
+    fn from_exposed(data: B) -> StuffedPtr<T, S, B> {
+        let provenance = match S::extract(data) {
+            Unstuffed::Ptr(addr) => sptr::from_exposed_addr_mut(addr),
+            Unstuffed::Other(_) => core::ptr::null_mut(),
+        };
+        // SAFETY: `data` was stuffed by a `StuffedPtr` with the same strategy and backend, and
+        //         the provenance of its pointer was exposed
+        unsafe { StuffedPtr::from_raw_stored(B::set_ptr(provenance, data)) }
+    }
+}
+
+// SAFETY: Like `AtomicPtr`, the pointers are only handed out as raw pointers. The `other` data
+//         can be loaded from all threads, so it must be `Send`.
+unsafe impl<T, S, B> Send for AtomicStuffedPtr<T, S, B>
+where
+    S: StuffingStrategy<B>,
+    S::Other: Send,
+    B: AtomicBackend,
+{
+}
+
+// SAFETY: See above
+unsafe impl<T, S, B> Sync for AtomicStuffedPtr<T, S, B>
+where
+    S: StuffingStrategy<B>,
+    S::Other: Send,
+    B: AtomicBackend,
+{
+}
+
+// loom atomics can only be used inside of `loom::model`
+#[cfg(all(test, not(loom)))]
+mod tests {
+    #![allow(non_snake_case, clippy::undocumented_unsafe_blocks)]
+
+    use core::sync::atomic::Ordering;
+
+    use paste::paste;
+
+    use super::AtomicStuffedPtr;
+    use crate::{
+        strategy::test_strategies::{EmptyInMax, LowBitU16},
+        StuffedPtr,
+    };
+
+    macro_rules! make_tests {
+        ($backend:ident) => {
+            paste! {
+                #[test]
+                fn [<load_store__ $backend>]() {
+                    let mut a = 1;
+                    let atomic: AtomicStuffedPtr<i32, EmptyInMax, $backend> =
+                        AtomicStuffedPtr::new(StuffedPtr::new_other(EmptyInMax));
+                    assert_eq!(atomic.load(Ordering::SeqCst).other(), Some(EmptyInMax));
+
+                    atomic.store(StuffedPtr::new_ptr(&mut a), Ordering::SeqCst);
+                    let ptr = atomic.load(Ordering::SeqCst).unwrap_ptr();
+                    unsafe { *ptr += 1 };
+                    assert_eq!(a, 2);
+                }
+
+                #[test]
+                fn [<swap__ $backend>]() {
+                    let mut a = 1;
+                    let atomic: AtomicStuffedPtr<i32, EmptyInMax, $backend> =
+                        AtomicStuffedPtr::new(StuffedPtr::new_ptr(&mut a));
+
+                    let old = atomic.swap(StuffedPtr::new_other(EmptyInMax), Ordering::SeqCst);
+                    unsafe { *old.unwrap_ptr() += 1 };
+                    assert_eq!(a, 2);
+                    assert!(atomic.load(Ordering::SeqCst).is_other());
+                }
+
+                #[test]
+                fn [<compare_exchange__ $backend>]() {
+                    let mut a = 1;
+                    let ptr: StuffedPtr<i32, EmptyInMax, $backend> = StuffedPtr::new_ptr(&mut a);
+                    let other: StuffedPtr<i32, EmptyInMax, $backend> = StuffedPtr::new_other(EmptyInMax);
+                    let atomic = AtomicStuffedPtr::new(ptr);
+
+                    assert_eq!(
+                        atomic.compare_exchange(other, ptr, Ordering::SeqCst, Ordering::SeqCst),
+                        Err(ptr)
+                    );
+                    assert_eq!(
+                        atomic.compare_exchange(ptr, other, Ordering::SeqCst, Ordering::SeqCst),
+                        Ok(ptr)
+                    );
+                    assert_eq!(atomic.load(Ordering::SeqCst), other);
+                }
+            }
+        };
+    }
+
+    #[test]
+    fn fetch_update() {
+        let atomic: AtomicStuffedPtr<u64, LowBitU16> =
+            AtomicStuffedPtr::new(StuffedPtr::new_other(0));
+
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..100 {
+                        let result = atomic.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |v| {
+                            v.other().map(|other| StuffedPtr::new_other(other + 1))
+                        });
+                        assert!(result.is_ok());
+                    }
+                });
+            }
+        });
+        assert_eq!(atomic.load(Ordering::SeqCst).other(), Some(400));
+
+        let mut a = 1;
+        atomic.store(StuffedPtr::new_ptr(&mut a), Ordering::SeqCst);
+        let result = atomic.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |v| {
+            v.other().map(|other| StuffedPtr::new_other(other + 1))
+        });
+        assert_eq!(result.map_err(|v| v.ptr()), Err(Some(&mut a as *mut u64)));
+    }
+
+    #[cfg(target_has_atomic = "64")]
+    make_tests!(u64);
+    make_tests!(usize);
+}
+
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use core::sync::atomic::Ordering;
+
+    use loom::{sync::Arc, thread};
+
+    use super::AtomicStuffedPtr;
+    use crate::{strategy::test_strategies::LowBitU16, StuffedPtr};
+
+    #[test]
+    fn racing_compare_exchange() {
+        loom::model(|| {
+            let atomic: Arc<AtomicStuffedPtr<u64, LowBitU16>> =
+                Arc::new(AtomicStuffedPtr::new(StuffedPtr::new_other(0)));
+
+            let threads: std::vec::Vec<_> = (0..2)
+                .map(|_| {
+                    let atomic = Arc::clone(&atomic);
+                    thread::spawn(move || {
+                        let mut current = atomic.load(Ordering::Acquire);
+                        loop {
+                            let other = current.other().expect("torn read");
+                            assert!(other <= 2, "torn read");
+                            let new = StuffedPtr::new_other(other + 1);
+                            match atomic.compare_exchange(
+                                current,
+                                new,
+                                Ordering::AcqRel,
+                                Ordering::Acquire,
+                            ) {
+                                Ok(_) => break,
+                                Err(actual) => current = actual,
+                            }
+                        }
+                    })
+                })
+                .collect();
+
+            for thread in threads {
+                thread.join().unwrap();
+            }
+
+            assert_eq!(atomic.load(Ordering::Acquire).other(), Some(2));
+        });
+    }
+
+    #[test]
+    fn racing_fetch_update() {
+        loom::model(|| {
+            let atomic: Arc<AtomicStuffedPtr<u64, LowBitU16>> =
+                Arc::new(AtomicStuffedPtr::new(StuffedPtr::new_other(0)));
+
+            let other_thread = {
+                let atomic = Arc::clone(&atomic);
+                thread::spawn(move || {
+                    atomic
+                        .fetch_update(Ordering::AcqRel, Ordering::Acquire, |v| {
+                            v.other().map(|other| StuffedPtr::new_other(other + 1))
+                        })
+                        .unwrap();
+                })
+            };
+
+            atomic
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |v| {
+                    v.other().map(|other| StuffedPtr::new_other(other + 1))
+                })
+                .unwrap();
+            other_thread.join().unwrap();
+
+            assert_eq!(atomic.load(Ordering::Acquire).other(), Some(2));
+        });
+    }
+}
@@ -1,5 +1,16 @@
+#[cfg(target_pointer_width = "64")]
+use core::num::NonZeroU64;
+use core::{mem, num::NonZeroUsize, ptr::NonNull};
+
 use sptr::Strict;
 
+// const generics are only parsed in a separate file, to keep the MSRV
+#[cfg(feature = "const-generics")]
+mod words;
+
+#[cfg(feature = "const-generics")]
+pub use self::words::Words;
+
 /// A backend where the stuffed pointer is stored. Must be bigger or equal to the pointer size.
 ///
 /// The `Backend` is a trait to define types that store the stuffed pointer. It's supposed to
@@ -11,16 +22,31 @@ use sptr::Strict;
 ///
 /// This trait is just exposed for convenience and flexibility, you are usually not expected to implement
 /// it yourself, although such occasions could occur (for example to have a bigger storage than `u128`
-/// or smaller storage that only works on 32-bit or 16-bit platforms.
+/// or smaller storage that only works on 32-bit or 16-bit platforms. For the former, there's
+/// `Words<N>` with the `const-generics` feature, which stores `N` pointer sized words.
+///
+/// `NonZeroUsize` (and `NonZeroU64` on 64 bit) can be used as a backend that stores a non-null
+/// pointer, so that `Option<StuffedPtr<T, S, NonZeroUsize>>` has the same size as a `usize`.
+/// Strategies for it can't produce a zero value by construction, which also means that they can't
+/// store null pointers.
 ///
 /// # Safety
 /// Implementers of this trait *must* keep provenance of pointers, so if a valid pointer address+provenance
 /// combination is set in `set_ptr`, `get_ptr` *must* return the exact same values and provenance.
-pub unsafe trait Backend {
+pub unsafe trait Backend: Sized {
     /// The underlying type where the data is stored. Often a tuple of a pointer (for the provenance)
     /// and some integers to fill up the bytes.
     type Stored: Copy;
 
+    /// The total number of bits of the backend.
+    const SIZE_BITS: u32 = (mem::size_of::<Self>() * 8) as u32;
+
+    /// The number of bits that are stored in the address of the pointer that keeps the provenance,
+    /// the rest is stored in integers. Which of the bits these are depends on the backend, the
+    /// tuple backends store the most significant bits in the pointer, and [`LsbInPtr`] the least
+    /// significant ones.
+    const PTR_BITS: u32 = (mem::size_of::<usize>() * 8) as u32;
+
     /// Get the pointer from the backed. Since the [`StuffingStrategy`](`crate::StuffingStrategy`)
     /// is able to use the full bytes to pack in the pointer address, the full address is returned
     /// in the second tuple field, as the integer. The provenance of the pointer is returned as
@@ -34,13 +60,46 @@ pub unsafe trait Backend {
     /// Get the integer value from the backend. Note that this *must not* be used to create a pointer,
     /// for that use [`Backend::get_ptr`] to keep the provenance.
     fn get_int(s: Self::Stored) -> Self;
+
+    /// Like [`Backend::set_ptr`], but returns `None` if the address can't be stored without
+    /// losing bits.
+    ///
+    /// The default implementation checks whether the address survives a round trip through
+    /// [`Backend::set_ptr`] and [`Backend::get_int`].
+    fn try_set_ptr(provenance: *mut (), addr: Self) -> Option<Self::Stored>
+    where
+        Self: PartialEq + Copy,
+    {
+        let stored = Self::set_ptr(provenance, addr);
+        if Self::get_int(stored) == addr {
+            Some(stored)
+        } else {
+            None
+        }
+    }
 }
 
+/// A backend that stores the least significant bits in the address of the pointer and the most
+/// significant bits in the integer filling up the rest, while the backend `B` on its own does it
+/// the other way around.
+///
+/// This matters because the pointer half of the storage is what keeps its provenance. With
+/// `LsbInPtr`, a pointer address stored in the low bits stays the real address of the stored
+/// pointer, and tag bits stored in the high bits only ever end up in the integer. Without it, the
+/// stored pointer only contains the high bits and its address is usually not a real address at
+/// all, although the provenance is still kept either way.
+///
+/// Strategies for this backend are implemented for `LsbInPtr<B>` and can access the integer
+/// through the field. It's only implemented for backends that are bigger than a pointer:
+/// `u128` on 64 bit, `u64` on 32 bit, and `u32` on 16 bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct LsbInPtr<B>(pub B);
+
 #[cfg(test)] // todo: this mustn't affect the msrv, fix this later
 mod backend_size_asserts {
     use core::mem;
 
-    use super::Backend;
+    use super::{Backend, LsbInPtr};
 
     #[allow(dead_code)] // :/
     const fn assert_same_size<A, B>() {
@@ -48,24 +107,35 @@ mod backend_size_asserts {
         assert!(has_equal_size);
     }
 
-    #[cfg(not(target_pointer_width = "16"))]
     const _: () = assert_same_size::<u128, <u128 as Backend>::Stored>();
     const _: () = assert_same_size::<u64, <u64 as Backend>::Stored>();
     const _: () = assert_same_size::<usize, <usize as Backend>::Stored>();
+    #[cfg(target_pointer_width = "16")]
+    const _: () = assert_same_size::<u32, <u32 as Backend>::Stored>();
+
+    #[cfg(target_pointer_width = "64")]
+    const _: () = assert_same_size::<u128, <LsbInPtr<u128> as Backend>::Stored>();
+    #[cfg(target_pointer_width = "32")]
+    const _: () = assert_same_size::<u64, <LsbInPtr<u64> as Backend>::Stored>();
+    #[cfg(target_pointer_width = "16")]
+    const _: () = assert_same_size::<u32, <LsbInPtr<u32> as Backend>::Stored>();
 }
 
 // SAFETY: We are careful around provenance
 unsafe impl Backend for usize {
     type Stored = *mut ();
 
+    #[inline]
     fn get_ptr(s: Self::Stored) -> (*mut (), Self) {
         (s, Strict::addr(s))
     }
 
+    #[inline]
     fn set_ptr(provenance: *mut (), addr: Self) -> Self::Stored {
         Strict::with_addr(provenance, addr)
     }
 
+    #[inline]
     fn get_int(s: Self::Stored) -> Self {
         Strict::addr(s)
     }
@@ -77,19 +147,57 @@ unsafe impl Backend for usize {
 unsafe impl Backend for u64 {
     type Stored = *mut ();
 
+    #[inline]
     fn get_ptr(s: Self::Stored) -> (*mut (), Self) {
         (s, Strict::addr(s) as u64)
     }
 
+    #[inline]
     fn set_ptr(provenance: *mut (), addr: Self) -> Self::Stored {
         Strict::with_addr(provenance, addr as usize)
     }
 
+    #[inline]
     fn get_int(s: Self::Stored) -> Self {
         Strict::addr(s) as u64
     }
 }
 
+macro_rules! impl_non_zero_backend {
+    ($ty:ident, $int:ident) => {
+        // SAFETY: We are careful around provenance
+        unsafe impl Backend for $ty {
+            // the address is never zero, so the pointer is never null
+            type Stored = NonNull<()>;
+
+            #[inline]
+            fn get_ptr(s: Self::Stored) -> (*mut (), Self) {
+                (s.as_ptr(), Self::get_int(s))
+            }
+
+            #[inline]
+            fn set_ptr(provenance: *mut (), addr: Self) -> Self::Stored {
+                let ptr = Strict::with_addr(provenance, addr.get() as usize);
+                // SAFETY: `addr` is not zero, so the pointer isn't null
+                unsafe { NonNull::new_unchecked(ptr) }
+            }
+
+            #[inline]
+            fn get_int(s: Self::Stored) -> Self {
+                let addr = Strict::addr(s.as_ptr()) as $int;
+                // SAFETY: `s` is not null, so its address isn't zero
+                unsafe { $ty::new_unchecked(addr) }
+            }
+        }
+    };
+}
+
+impl_non_zero_backend!(NonZeroUsize, usize);
+
+// on 64 bit, we can just treat u64/usize interchangeably, like for `u64`
+#[cfg(target_pointer_width = "64")]
+impl_non_zero_backend!(NonZeroU64, u64);
+
 macro_rules! impl_backend_2_tuple {
     (impl for $ty:ty { (*mut (), $int:ident), $num:expr }) => {
         // SAFETY: We are careful around provenance
@@ -98,16 +206,19 @@ macro_rules! impl_backend_2_tuple {
 
             type Stored = (*mut (), $int);
 
+            #[inline]
             fn get_ptr(s: Self::Stored) -> (*mut (), Self) {
                 (s.0, Self::get_int(s))
             }
 
+            #[inline]
             fn set_ptr(provenance: *mut (), addr: Self) -> Self::Stored {
                 let ptr_addr = (addr >> $num) as usize;
                 let int_addr = addr as $int; // truncate it
                 (Strict::with_addr(provenance, ptr_addr), int_addr)
             }
 
+            #[inline]
             fn get_int(s: Self::Stored) -> Self {
                 let ptr_addr = Strict::addr(s.0) as $int;
                 (<$ty>::from(ptr_addr) << $num) | <$ty>::from(s.1)
@@ -116,6 +227,35 @@ macro_rules! impl_backend_2_tuple {
     };
 }
 
+macro_rules! impl_lsb_backend_2_tuple {
+    (impl for $ty:ty { (*mut (), $int:ident), $num:expr }) => {
+        // SAFETY: We are careful around provenance
+        unsafe impl Backend for LsbInPtr<$ty> {
+            // this one keeps the LSB in the pointer address, and the MSB in the integer
+
+            type Stored = (*mut (), $int);
+
+            #[inline]
+            fn get_ptr(s: Self::Stored) -> (*mut (), Self) {
+                (s.0, Self::get_int(s))
+            }
+
+            #[inline]
+            fn set_ptr(provenance: *mut (), addr: Self) -> Self::Stored {
+                let ptr_addr = addr.0 as usize; // truncate it
+                let int_addr = (addr.0 >> $num) as $int;
+                (Strict::with_addr(provenance, ptr_addr), int_addr)
+            }
+
+            #[inline]
+            fn get_int(s: Self::Stored) -> Self {
+                let ptr_addr = Strict::addr(s.0) as $int;
+                LsbInPtr((<$ty>::from(s.1) << $num) | <$ty>::from(ptr_addr))
+            }
+        }
+    };
+}
+
 /// num1 is ptr-sized, num2 is 2*ptr sized
 #[cfg_attr(target_pointer_width = "64", allow(unused))] // not required on 64 bit
 macro_rules! impl_backend_3_tuple {
@@ -126,10 +266,12 @@ macro_rules! impl_backend_3_tuple {
 
             type Stored = (*mut (), $int1, $int2);
 
+            #[inline]
             fn get_ptr(s: Self::Stored) -> (*mut (), Self) {
                 (s.0, Self::get_int(s))
             }
 
+            #[inline]
             fn set_ptr(provenance: *mut (), addr: Self) -> Self::Stored {
                 let ptr_addr = (addr >> ($num1 + $num2)) as usize;
                 let num1_addr = (addr >> $num2) as $int1; // truncate it
@@ -141,6 +283,7 @@ macro_rules! impl_backend_3_tuple {
                 )
             }
 
+            #[inline]
             fn get_int(s: Self::Stored) -> Self {
                 let ptr_addr = Strict::addr(s.0) as $ty;
                 let num1_addr = s.1 as $ty;
@@ -151,6 +294,50 @@ macro_rules! impl_backend_3_tuple {
     };
 }
 
+/// num1 is ptr-sized, num2 is 2*ptr sized, num3 is 4*ptr sized
+#[cfg_attr(not(target_pointer_width = "16"), allow(unused))] // only required on 16 bit
+macro_rules! impl_backend_4_tuple {
+    (impl for $ty:ty { (*mut (), $int1:ident, $int2:ident, $int3:ident), $num1:expr, $num2:expr, $num3:expr }) => {
+        // SAFETY: We are careful around provenance
+        unsafe impl Backend for $ty {
+            // this one keeps the MSB in the pointer address, then int1, int2 and the LSB in int3
+
+            type Stored = (*mut (), $int1, $int2, $int3);
+
+            #[inline]
+            fn get_ptr(s: Self::Stored) -> (*mut (), Self) {
+                (s.0, Self::get_int(s))
+            }
+
+            #[inline]
+            fn set_ptr(provenance: *mut (), addr: Self) -> Self::Stored {
+                let ptr_addr = (addr >> ($num1 + $num2 + $num3)) as usize;
+                let num1_addr = (addr >> ($num2 + $num3)) as $int1; // truncate it
+                let num2_addr = (addr >> $num3) as $int2; // truncate it
+                let num3_addr = addr as $int3; // truncate it
+                (
+                    Strict::with_addr(provenance, ptr_addr),
+                    num1_addr,
+                    num2_addr,
+                    num3_addr,
+                )
+            }
+
+            #[inline]
+            fn get_int(s: Self::Stored) -> Self {
+                let ptr_addr = Strict::addr(s.0) as $ty;
+                let num1_addr = s.1 as $ty;
+                let num2_addr = s.2 as $ty;
+                let num3_addr = s.3 as $ty;
+                (ptr_addr << ($num1 + $num2 + $num3))
+                    | (num1_addr << ($num2 + $num3))
+                    | (num2_addr << $num3)
+                    | num3_addr
+            }
+        }
+    };
+}
+
 #[cfg(target_pointer_width = "64")]
 impl_backend_2_tuple!(impl for u128 { (*mut (), u64), 64 });
 
@@ -160,7 +347,263 @@ impl_backend_2_tuple!(impl for u64 { (*mut (), u32), 32 });
 #[cfg(target_pointer_width = "32")]
 impl_backend_3_tuple!(impl for u128 { (*mut (), u32, u64), 32, 64 });
 
+#[cfg(target_pointer_width = "16")]
+impl_backend_2_tuple!(impl for u32 { (*mut (), u16), 16 });
+
 #[cfg(target_pointer_width = "16")]
 impl_backend_3_tuple!(impl for u64 { (*mut (), u16, u32), 16, 32 });
 
-// no 128 on 16 bit for now
+#[cfg(target_pointer_width = "16")]
+impl_backend_4_tuple!(impl for u128 { (*mut (), u16, u32, u64), 16, 32, 64 });
+
+#[cfg(target_pointer_width = "64")]
+impl_lsb_backend_2_tuple!(impl for u128 { (*mut (), u64), 64 });
+
+#[cfg(target_pointer_width = "32")]
+impl_lsb_backend_2_tuple!(impl for u64 { (*mut (), u32), 32 });
+
+#[cfg(target_pointer_width = "16")]
+impl_lsb_backend_2_tuple!(impl for u32 { (*mut (), u16), 16 });
+
+#[cfg(all(test, target_pointer_width = "64"))]
+mod tests {
+    #![allow(clippy::undocumented_unsafe_blocks)]
+
+    use core::{mem, num::NonZeroUsize};
+    use std::vec::Vec;
+
+    use sptr::Strict;
+
+    use super::LsbInPtr;
+    use crate::{
+        test_util::assert_backend_roundtrip, Backend, StuffedPtr, StuffingStrategy, Unstuffed,
+    };
+
+    // stores a `u16` with the lowest bit set, so it's never zero
+    struct NonZeroLowBit;
+
+    impl StuffingStrategy<NonZeroUsize> for NonZeroLowBit {
+        type Other = u16;
+
+        fn stuff_other(inner: Self::Other) -> NonZeroUsize {
+            NonZeroUsize::new((usize::from(inner) << 1) | 1).unwrap()
+        }
+
+        fn extract(data: NonZeroUsize) -> Unstuffed<usize, Self::Other> {
+            match data.get() & 1 == 1 {
+                true => Unstuffed::Other((data.get() >> 1) as u16),
+                false => Unstuffed::Ptr(data.get()),
+            }
+        }
+
+        fn stuff_ptr(addr: usize) -> NonZeroUsize {
+            NonZeroUsize::new(addr).expect("null pointer")
+        }
+    }
+
+    #[test]
+    fn non_zero() {
+        type Stuffed = StuffedPtr<u64, NonZeroLowBit, NonZeroUsize>;
+        assert_eq!(mem::size_of::<Option<Stuffed>>(), mem::size_of::<usize>());
+
+        let mut a = 1_u64;
+        let stuffed: Stuffed = StuffedPtr::new_ptr(&mut a);
+        unsafe { *stuffed.unwrap_ptr() += 1 };
+        assert_eq!(a, 2);
+
+        let stuffed: Stuffed = StuffedPtr::new_other(0);
+        assert_eq!(stuffed.other(), Some(0));
+    }
+
+    #[test]
+    fn roundtrip() {
+        let addrs = [0, 1, 0xdead_beef, usize::MAX];
+        assert_backend_roundtrip::<usize>(&addrs);
+
+        let addrs = [0, 1, 0xdead_beef, u64::MAX];
+        assert_backend_roundtrip::<u64>(&addrs);
+
+        let addrs = [
+            0,
+            1,
+            0xdead_beef,
+            u128::from(u64::MAX) + 1,
+            0x0123_4567_89ab_cdef_fedc_ba98_7654_3210,
+            u128::MAX,
+        ];
+        assert_backend_roundtrip::<u128>(&addrs);
+        let addrs: Vec<_> = addrs.iter().map(|&addr| LsbInPtr(addr)).collect();
+        assert_backend_roundtrip::<LsbInPtr<u128>>(&addrs);
+
+        let addrs: Vec<_> = [1, 0xdead_beef, usize::MAX]
+            .iter()
+            .map(|&addr| NonZeroUsize::new(addr).unwrap())
+            .collect();
+        assert_backend_roundtrip::<NonZeroUsize>(&addrs);
+    }
+
+    // only stores a `usize` worth of a `u128`
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Truncating(u128);
+
+    unsafe impl Backend for Truncating {
+        type Stored = *mut ();
+
+        fn get_ptr(s: Self::Stored) -> (*mut (), Self) {
+            (s, Self::get_int(s))
+        }
+
+        fn set_ptr(provenance: *mut (), addr: Self) -> Self::Stored {
+            Strict::with_addr(provenance, addr.0 as usize)
+        }
+
+        fn get_int(s: Self::Stored) -> Self {
+            Truncating(Strict::addr(s) as u128)
+        }
+    }
+
+    #[test]
+    fn try_set_ptr() {
+        let mut a = 1_u8;
+        let provenance = &mut a as *mut u8 as *mut ();
+
+        let stored = <Truncating as Backend>::try_set_ptr(provenance, Truncating(5));
+        assert_eq!(stored.map(Strict::addr), Some(5));
+        let stored = <Truncating as Backend>::try_set_ptr(provenance, Truncating(u128::MAX));
+        assert_eq!(stored, None);
+
+        assert!(<u128 as Backend>::try_set_ptr(provenance, u128::MAX).is_some());
+    }
+
+    #[test]
+    fn bits() {
+        assert_eq!(<usize as Backend>::SIZE_BITS, 64);
+        assert_eq!(<usize as Backend>::PTR_BITS, 64);
+        assert_eq!(<u64 as Backend>::SIZE_BITS, 64);
+        assert_eq!(<u64 as Backend>::PTR_BITS, 64);
+        assert_eq!(<u128 as Backend>::SIZE_BITS, 128);
+        assert_eq!(<u128 as Backend>::PTR_BITS, 64);
+        assert_eq!(<LsbInPtr<u128> as Backend>::SIZE_BITS, 128);
+        assert_eq!(<LsbInPtr<u128> as Backend>::PTR_BITS, 64);
+    }
+
+    // stores a `u32` in the upper half, marked by the highest bit
+    struct HighU32;
+
+    impl StuffingStrategy<LsbInPtr<u128>> for HighU32 {
+        type Other = u32;
+
+        fn stuff_other(inner: Self::Other) -> LsbInPtr<u128> {
+            LsbInPtr((1 << 127) | (u128::from(inner) << 64))
+        }
+
+        fn extract(data: LsbInPtr<u128>) -> Unstuffed<usize, Self::Other> {
+            match data.0 >> 127 == 1 {
+                true => Unstuffed::Other((data.0 >> 64) as u32),
+                false => Unstuffed::Ptr(data.0 as usize),
+            }
+        }
+
+        fn stuff_ptr(addr: usize) -> LsbInPtr<u128> {
+            LsbInPtr(addr as u128)
+        }
+    }
+
+    #[test]
+    fn lsb_in_ptr() {
+        let mut a = 1_u64;
+        let a_ptr: *mut u64 = &mut a;
+        let stuffed: StuffedPtr<u64, HighU32, LsbInPtr<u128>> = StuffedPtr::new_ptr(a_ptr);
+
+        // the address stays in the pointer half
+        let (ptr, int) = stuffed.raw_stored();
+        assert_eq!(Strict::addr(ptr), Strict::addr(a_ptr));
+        assert_eq!(int, 0);
+
+        unsafe { *stuffed.unwrap_ptr() += 1 };
+        assert_eq!(a, 2);
+
+        let stuffed: StuffedPtr<u64, HighU32, LsbInPtr<u128>> = StuffedPtr::new_other(u32::MAX);
+        let (ptr, int) = stuffed.raw_stored();
+        assert_eq!(Strict::addr(ptr), 0);
+        assert_eq!(int, (1 << 63) | u64::from(u32::MAX));
+        assert_eq!(stuffed.other(), Some(u32::MAX));
+    }
+}
+
+// The tuple backends only use shifts and casts, which operate on values and not on their bytes, so
+// they don't depend on the endianness of the target. Big endian 32 bit targets run these tests in CI.
+#[cfg(all(test, not(target_pointer_width = "64")))]
+mod tuple_tests {
+    use super::Backend;
+
+    macro_rules! round_trip {
+        ($name:ident, $ty:ident, $value:expr) => {
+            #[test]
+            fn $name() {
+                let mut a = 0_u8;
+                let provenance = &mut a as *mut u8 as *mut ();
+
+                let stored = <$ty as Backend>::set_ptr(provenance, $value);
+                assert_eq!(<$ty as Backend>::get_int(stored), $value);
+                assert_eq!(<$ty as Backend>::get_ptr(stored).1, $value);
+            }
+        };
+    }
+
+    #[cfg(target_pointer_width = "32")]
+    round_trip!(u128_max, u128, u128::max_value());
+    #[cfg(target_pointer_width = "32")]
+    round_trip!(
+        u128_pattern,
+        u128,
+        0x0123_4567_89ab_cdef_fedc_ba98_7654_3210
+    );
+    #[cfg(target_pointer_width = "32")]
+    round_trip!(u64_pattern, u64, 0x0123_4567_89ab_cdef);
+    #[cfg(target_pointer_width = "16")]
+    round_trip!(u64_max, u64, u64::max_value());
+    #[cfg(target_pointer_width = "16")]
+    round_trip!(u64_pattern, u64, 0x0123_4567_89ab_cdef);
+    #[cfg(target_pointer_width = "16")]
+    round_trip!(u32_pattern, u32, 0x0123_4567);
+    #[cfg(target_pointer_width = "16")]
+    round_trip!(u128_max, u128, u128::max_value());
+    #[cfg(target_pointer_width = "16")]
+    round_trip!(
+        u128_pattern,
+        u128,
+        0x0123_4567_89ab_cdef_fedc_ba98_7654_3210
+    );
+
+    #[test]
+    #[cfg(target_pointer_width = "32")]
+    fn u128_layout() {
+        use sptr::Strict;
+
+        let mut a = 0_u8;
+        let provenance = &mut a as *mut u8 as *mut ();
+
+        let stored =
+            <u128 as Backend>::set_ptr(provenance, 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+        assert_eq!(Strict::addr(stored.0), 0x0123_4567);
+        assert_eq!(stored.1, 0x89ab_cdef);
+        assert_eq!(stored.2, 0xfedc_ba98_7654_3210);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "16")]
+    fn u128_layout() {
+        use sptr::Strict;
+
+        let mut a = 0_u8;
+        let provenance = &mut a as *mut u8 as *mut ();
+
+        let stored =
+            <u128 as Backend>::set_ptr(provenance, 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+        assert_eq!(Strict::addr(stored.0), 0x0123);
+        assert_eq!(stored.1, 0x4567);
+        assert_eq!(stored.2, 0x89ab_cdef);
+        assert_eq!(stored.3, 0xfedc_ba98_7654_3210);
+    }
+}
@@ -0,0 +1,129 @@
+use sptr::Strict;
+
+use super::Backend;
+
+/// A backend of `N` pointer sized words, for storage bigger than `u128`.
+///
+/// Only available with the `const-generics` feature, which requires Rust 1.57.
+///
+/// The words are ordered from the least to the most significant one, like in a little endian
+/// integer. The most significant word is stored in the address of the pointer that keeps the
+/// provenance, like for the tuple backends, and the other words are stored as pointers without
+/// provenance. `N` must be at least 1, using it with `N = 0` is a compile error.
+///
+/// ```
+/// use stuff::{Backend, Words};
+///
+/// let mut a = 1_u8;
+/// let provenance = &mut a as *mut u8 as *mut ();
+///
+/// let wide = Words([1, 2, 3]);
+/// let stored = Words::set_ptr(provenance, wide);
+/// assert_eq!(Words::get_int(stored), wide);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Words<const N: usize>(pub [usize; N]);
+
+impl<const N: usize> Words<N> {
+    const ASSERT_NOT_EMPTY: () = assert!(N >= 1, "Words needs at least one word");
+}
+
+// SAFETY: We are careful around provenance
+unsafe impl<const N: usize> Backend for Words<N> {
+    // this one keeps the most significant word in the pointer address, and all others in pointers
+    // without provenance, since arrays of `N - 1` words can't be expressed
+
+    type Stored = [*mut (); N];
+
+    #[inline]
+    fn get_ptr(s: Self::Stored) -> (*mut (), Self) {
+        let () = Self::ASSERT_NOT_EMPTY;
+        (s[N - 1], Self::get_int(s))
+    }
+
+    #[inline]
+    fn set_ptr(provenance: *mut (), addr: Self) -> Self::Stored {
+        let () = Self::ASSERT_NOT_EMPTY;
+        let mut stored = [core::ptr::null_mut(); N];
+        for (stored, &word) in stored.iter_mut().zip(addr.0.iter()) {
+            *stored = sptr::invalid_mut(word);
+        }
+        stored[N - 1] = Strict::with_addr(provenance, addr.0[N - 1]);
+        stored
+    }
+
+    #[inline]
+    fn get_int(s: Self::Stored) -> Self {
+        let mut words = [0; N];
+        for (word, &stored) in words.iter_mut().zip(s.iter()) {
+            *word = Strict::addr(stored);
+        }
+        Words(words)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::undocumented_unsafe_blocks)]
+
+    use core::mem;
+
+    use super::Words;
+    use crate::{
+        test_util::assert_backend_roundtrip, Backend, StuffedPtr, StuffingStrategy, Unstuffed,
+    };
+
+    // stores two words of other data, and a tag in the most significant word
+    struct Wide;
+
+    impl StuffingStrategy<Words<3>> for Wide {
+        type Other = [usize; 2];
+
+        fn stuff_other(inner: Self::Other) -> Words<3> {
+            Words([inner[0], inner[1], 1])
+        }
+
+        fn extract(data: Words<3>) -> Unstuffed<usize, Self::Other> {
+            match data.0[2] {
+                0 => Unstuffed::Ptr(data.0[0]),
+                _ => Unstuffed::Other([data.0[0], data.0[1]]),
+            }
+        }
+
+        fn stuff_ptr(addr: usize) -> Words<3> {
+            Words([addr, 0, 0])
+        }
+    }
+
+    #[test]
+    fn size() {
+        assert_eq!(
+            mem::size_of::<Words<3>>(),
+            mem::size_of::<<Words<3> as Backend>::Stored>()
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        assert_backend_roundtrip::<Words<1>>(&[Words([0]), Words([usize::MAX])]);
+        assert_backend_roundtrip::<Words<3>>(&[
+            Words([0, 0, 0]),
+            Words([1, 2, 3]),
+            Words([usize::MAX, 0, usize::MAX]),
+            Words([usize::MAX; 3]),
+        ]);
+    }
+
+    #[test]
+    fn wide_other() {
+        let mut a = 1_u64;
+        let stuffed: StuffedPtr<u64, Wide, Words<3>> = StuffedPtr::new_ptr(&mut a);
+        unsafe { *stuffed.unwrap_ptr() += 1 };
+        assert_eq!(a, 2);
+
+        let wide = [usize::MAX, 0x0123_4567];
+        let stuffed: StuffedPtr<u64, Wide, Words<3>> = StuffedPtr::new_other(wide);
+        assert_eq!(stuffed.other(), Some(wide));
+        assert_eq!(stuffed.ptr(), None);
+    }
+}
@@ -0,0 +1,53 @@
+use bytemuck::Zeroable;
+
+use crate::{Backend, StuffedPtr, StuffingStrategy};
+
+/// A marker trait for strategies where the all-zero bit pattern of the backend is a valid value,
+/// for example a null pointer. It allows [`StuffedPtr`] to implement [`bytemuck::Zeroable`].
+///
+/// Only available with the `bytemuck` feature.
+///
+/// # Safety
+/// [`StuffingStrategy::extract`] must return a valid value for the all-zero backend value: either
+/// a pointer, or `other` data that is valid for `S::Other`.
+pub unsafe trait ZeroableStrategy<B>: StuffingStrategy<B> {}
+
+// SAFETY: `()` treats every value as a pointer, zero is a null pointer
+unsafe impl<B> ZeroableStrategy<B> for () where (): StuffingStrategy<B> {}
+
+/// The all-zero `StuffedPtr` is the value that the strategy extracts from a zero backend, for
+/// `()` that's a null pointer.
+///
+/// Only available with the `bytemuck` feature.
+// SAFETY: `StuffedPtr` is `#[repr(transparent)]` to `B::Stored`, which is zeroable, and the
+// strategy promises that the zero value is valid
+unsafe impl<T, S, B> Zeroable for StuffedPtr<T, S, B>
+where
+    S: ZeroableStrategy<B>,
+    B: Backend,
+    B::Stored: Zeroable,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec;
+
+    use bytemuck::Zeroable;
+
+    use crate::StuffedPtr;
+
+    #[test]
+    fn zeroed() {
+        let ptrs = vec![StuffedPtr::<u64, ()>::zeroed(); 4];
+        for ptr in ptrs {
+            assert!(ptr.unwrap_ptr().is_null());
+        }
+
+        let ptr = StuffedPtr::<u64, (), u64>::zeroed();
+        assert!(ptr.unwrap_ptr().is_null());
+
+        let ptr = StuffedPtr::<u64, (), u128>::zeroed();
+        assert!(ptr.unwrap_ptr().is_null());
+    }
+}
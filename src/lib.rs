@@ -25,7 +25,8 @@
 //! as our "object" type).
 //!
 //! See [crafting interpreters](https://craftinginterpreters.com/optimization.html#nan-boxing)
-//! for more details.
+//! for more details. A ready-made version of this strategy is available as
+//! [`strategies::NanBoxStrategy`].
 //! ```
 //! use std::collections::HashMap;
 //! # use std::convert::{TryFrom, TryInto};
@@ -75,32 +76,94 @@
 //! let boxed = Box::new(object);
 //! let ptr: Value = StuffedPtr::new_ptr(Box::into_raw(boxed));
 //!
-//! let object = unsafe { &*ptr.ptr().unwrap() };
+//! let object = unsafe { &*ptr.unwrap_ptr() };
 //! assert_eq!(object.get("a"), Some(&457));
 //!
-//! drop(unsafe { Box::from_raw(ptr.ptr().unwrap()) });
+//! drop(unsafe { Box::from_raw(ptr.unwrap_ptr()) });
 //!
 //! // be careful, `ptr` is a dangling pointer now!
 //! ```
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
 #[cfg(test)]
 extern crate std;
 
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls;
+#[cfg(feature = "atomic")]
+mod atomic;
 mod backend;
+#[cfg(feature = "bytemuck")]
+mod bytemuck_impls;
+mod raw_hash;
+#[cfg(feature = "serde")]
+mod serde_impls;
+pub mod strategies;
 mod strategy;
 
-#[cfg(any())]
+#[cfg(feature = "tagged")]
 mod tag;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 
 use core::{
-    fmt::{Debug, Formatter},
+    cmp::Ordering,
+    fmt::{Binary, Debug, Formatter, LowerHex, Pointer},
     hash::{Hash, Hasher},
     marker::PhantomData,
+    ptr::NonNull,
 };
 
 use sptr::Strict;
+#[cfg(feature = "derive")]
+pub use stuff_derive::StuffingStrategy;
+#[cfg(all(feature = "derive", feature = "tagged"))]
+pub use stuff_derive::TagBits;
 
-pub use crate::{backend::Backend, either::Unstuffed, strategy::StuffingStrategy};
+#[cfg(feature = "atomic")]
+pub use crate::atomic::{AtomicBackend, AtomicStuffedPtr};
+#[cfg(feature = "const-generics")]
+pub use crate::backend::Words;
+#[cfg(feature = "bytemuck")]
+pub use crate::bytemuck_impls::ZeroableStrategy;
+#[cfg(all(feature = "tagged", feature = "const-generics"))]
+pub use crate::tag::AlignTag;
+#[cfg(feature = "tagged")]
+pub use crate::tag::{BoolTag, TaggedPtr, TaggingStrategy};
+pub use crate::{
+    backend::{Backend, LsbInPtr},
+    either::Unstuffed,
+    raw_hash::{RawHashable, RawHashed},
+    strategy::StuffingStrategy,
+};
+
+// `const fn`s with trait bounds require Rust 1.61, so it's only `const` with the `const-fn` feature
+macro_rules! from_raw_stored {
+    ($($const:tt)?) => {
+        /// Create a `StuffedPtr` from the underlying storage of the backend.
+        ///
+        /// With the `const-fn` feature, this is a `const fn`. It can then be used to create
+        /// `StuffedPtr`s containing `other` data in constants, for example for lookup tables. Since
+        /// the methods of [`StuffingStrategy`] and [`Backend`] aren't `const`, the stored value has
+        /// to be built by hand in that case. For the `usize` backend, that's
+        /// `sptr::invalid_mut(stuffed)`, where `stuffed` is the value that
+        /// [`StuffingStrategy::stuff_other`] would return.
+        ///
+        /// # Safety
+        /// `stored` must be a valid stored value for the strategy `S` and the backend `B`, for
+        /// example one obtained from [`StuffedPtr::raw_stored`] or [`StuffedPtr::into_raw_stored`]
+        /// on a `StuffedPtr` with the same type parameters.
+        ///
+        /// **The provenance of the stored pointer must be preserved.** The stored value must not be
+        /// created from integers alone (for example after a round trip through an integer type),
+        /// otherwise pointers obtained from it are not valid for any memory accesses. Values
+        /// containing `other` data don't have a pointer, so this doesn't apply to them.
+        pub $($const)? unsafe fn from_raw_stored(stored: B::Stored) -> Self {
+            StuffedPtr(stored, PhantomData)
+        }
+    };
+}
 
 /// A union of a pointer or some `other` data, bitpacked into a value with the size depending on
 /// `B`. It defaults to `usize`, meaning pointer sized, but `u64` and `u128` are also provided
@@ -127,13 +190,92 @@ where
     B: Backend,
 {
     /// Create a new `StuffedPtr` from a pointer
+    ///
+    /// # Panics
+    /// Panics if the strategy panics in [`StuffingStrategy::stuff_ptr`], for example because the
+    /// address doesn't fit into a custom backend. See [`StuffedPtr::try_new_ptr`] for a fallible
+    /// version.
+    #[inline]
     pub fn new_ptr(ptr: *mut T) -> Self {
         let addr = Strict::addr(ptr);
         let stuffed = S::stuff_ptr(addr);
         StuffedPtr(B::set_ptr(ptr as *mut (), stuffed), PhantomData)
     }
 
+    /// Create a new `StuffedPtr` from a pointer, or `None` if the strategy can't stuff the
+    /// address or the backend can't store the stuffed address without losing bits. See
+    /// [`StuffingStrategy::try_stuff_ptr`] and [`Backend::try_set_ptr`].
+    pub fn try_new_ptr(ptr: *mut T) -> Option<Self>
+    where
+        B: PartialEq + Copy,
+    {
+        let addr = Strict::addr(ptr);
+        let stuffed = S::try_stuff_ptr(addr)?;
+        B::try_set_ptr(ptr as *mut (), stuffed).map(|stored| StuffedPtr(stored, PhantomData))
+    }
+
+    /// Create a new `StuffedPtr` from a [`NonNull`] pointer
+    pub fn new_non_null(ptr: NonNull<T>) -> Self {
+        Self::new_ptr(ptr.as_ptr())
+    }
+
+    /// Create a new `StuffedPtr` from a null pointer, like `new_ptr(core::ptr::null_mut())`.
+    ///
+    /// Whether the result contains a pointer depends on the strategy: with `()`, it's a null
+    /// pointer, but with [`NullIsNone`](`strategies::NullIsNone`), it's `other` data.
+    pub fn null_ptr() -> Self {
+        Self::new_ptr(core::ptr::null_mut())
+    }
+
+    /// Create a new `StuffedPtr` from a shared reference. The pointer keeps the provenance of the
+    /// reference.
+    ///
+    /// The pointer is only valid for reads, writing through it is undefined behavior even though
+    /// it's a `*mut T`. Use [`StuffedPtr::from_mut`] if you need to write.
+    pub fn from_ref(r: &T) -> Self {
+        Self::new_ptr(r as *const T as *mut T)
+    }
+
+    /// Create a new `StuffedPtr` from a mutable reference. The pointer keeps the provenance of the
+    /// reference.
+    pub fn from_mut(r: &mut T) -> Self {
+        Self::new_ptr(r)
+    }
+
+    /// Create a new `StuffedPtr` containing a dangling, but well-aligned and non-null pointer,
+    /// like [`NonNull::dangling`].
+    ///
+    /// This is useful as a placeholder, for example for empty slots where a null pointer already
+    /// has a different meaning. Like any pointer, it is never confused with an `other` as long
+    /// as the strategy is sound.
+    pub fn dangling() -> Self {
+        Self::new_non_null(NonNull::dangling())
+    }
+
+    /// Create a new `StuffedPtr` from a [`Box`](alloc::boxed::Box), taking ownership of the
+    /// allocation. It can be turned back into a box using [`StuffedPtr::into_box`].
+    ///
+    /// Only available with the `alloc` feature.
+    #[cfg(feature = "alloc")]
+    pub fn from_box(boxed: alloc::boxed::Box<T>) -> Self {
+        Self::new_ptr(alloc::boxed::Box::into_raw(boxed))
+    }
+
+    /// Turn the pointer back into a [`Box`](alloc::boxed::Box), or `None` if it contains `other`
+    /// data.
+    ///
+    /// Only available with the `alloc` feature.
+    ///
+    /// # Safety
+    /// The pointer must have been created from [`Box::into_raw`](alloc::boxed::Box::into_raw),
+    /// for example using [`StuffedPtr::from_box`], and it must not be turned into a box twice.
+    #[cfg(feature = "alloc")]
+    pub unsafe fn into_box(self) -> Option<alloc::boxed::Box<T>> {
+        self.ptr().map(|ptr| alloc::boxed::Box::from_raw(ptr))
+    }
+
     /// Create a new `StuffPtr` from `other` data
+    #[inline]
     pub fn new_other(other: S::Other) -> Self {
         // this doesn't have any provenance, which is ok, since it's never a pointer anyways.
         // if the user calls `set_ptr` it will use the new provenance from that ptr
@@ -142,31 +284,351 @@ where
         StuffedPtr(B::set_ptr(ptr, other), PhantomData)
     }
 
+    /// Set a new pointer, overwriting the current pointer or `other` data.
+    ///
+    /// The new pointer brings its own provenance. Since `other` data is always `Copy`, nothing
+    /// needs to be dropped.
+    pub fn set_ptr(&mut self, ptr: *mut T) {
+        *self = Self::new_ptr(ptr);
+    }
+
+    /// Set new `other` data, returning the previous `other` data, or `None` if it contained a
+    /// pointer.
+    pub fn replace_other(&mut self, other: S::Other) -> Option<S::Other> {
+        let old = self.other();
+        *self = Self::new_other(other);
+        old
+    }
+
+    /// Take the `other` data out, leaving a null pointer in its place. If it contains a pointer,
+    /// `None` is returned and the pointer is left untouched.
+    pub fn take_other(&mut self) -> Option<S::Other> {
+        let old = self.other()?;
+        *self = Self::new_ptr(core::ptr::null_mut());
+        Some(old)
+    }
+
+    /// Swap the contents of two `StuffedPtr`s, keeping the provenance of the pointers.
+    ///
+    /// Since the `other` data is `Copy`, neither of the values is ever dropped.
+    pub fn swap(&mut self, other: &mut Self) {
+        core::mem::swap(&mut self.0, &mut other.0);
+    }
+
+    /// Replace the contents with `new`, returning the previous value, like [`core::mem::replace`].
+    ///
+    /// Unlike [`StuffedPtr::replace_other`], this works for pointers too and keeps their
+    /// provenance. Since the `other` data is `Copy`, neither of the values is ever dropped.
+    pub fn replace(&mut self, new: Self) -> Self {
+        core::mem::replace(self, new)
+    }
+
+    /// Get the `other` data, or replace the pointer with `default` and return that if it contains
+    /// a pointer. Like [`Option::get_or_insert`], but the `other` data is returned by value.
+    pub fn get_or_insert_other(&mut self, default: S::Other) -> S::Other {
+        self.get_or_insert_other_with(|| default)
+    }
+
+    /// Get the `other` data, or replace the pointer with the result of `f` and return that if it
+    /// contains a pointer. Like [`Option::get_or_insert_with`], but the `other` data is returned
+    /// by value.
+    pub fn get_or_insert_other_with(&mut self, f: impl FnOnce() -> S::Other) -> S::Other {
+        match self.other() {
+            Some(other) => other,
+            None => {
+                let other = f();
+                *self = Self::new_other(other);
+                other
+            }
+        }
+    }
+
+    /// Apply `f` to the `other` data and stuff the result back in. Does nothing if it contains a
+    /// pointer.
+    pub fn map_other_in_place(&mut self, f: impl FnOnce(S::Other) -> S::Other) {
+        if let Some(other) = self.other() {
+            *self = Self::new_other(f(other));
+        }
+    }
+
+    /// Modify the `other` data through a mutable reference and stuff it back in. Returns `true`
+    /// if it contained `other` data, and `false` without calling `f` if it contains a pointer.
+    ///
+    /// ```
+    /// use stuff::{strategies::NanBoxStrategy, StuffedPtr};
+    ///
+    /// let mut value: StuffedPtr<(), NanBoxStrategy, u64> = StuffedPtr::new_other(1.5);
+    /// assert!(value.modify_other(|float| *float *= 2.0));
+    /// assert_eq!(value.other(), Some(3.0));
+    /// ```
+    pub fn modify_other(&mut self, f: impl FnOnce(&mut S::Other)) -> bool {
+        match self.other() {
+            Some(mut other) => {
+                f(&mut other);
+                *self = Self::new_other(other);
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Get the pointer data, or `None` if it contains `other` data
+    #[inline]
     pub fn ptr(&self) -> Option<*mut T> {
         let (provenance, stored) = B::get_ptr(self.0);
         let addr = S::extract(stored).ptr()?;
         Some(Strict::with_addr(provenance as *mut T, addr))
     }
 
+    /// Get the pointer data as a [`NonNull`] pointer, or `None` if it contains `other` data or a
+    /// null pointer
+    pub fn non_null(&self) -> Option<NonNull<T>> {
+        NonNull::new(self.ptr()?)
+    }
+
+    /// Apply `f` to the address of the pointer, keeping its provenance, or do nothing if it
+    /// contains `other` data. See [`sptr::Strict::map_addr`].
+    ///
+    /// ```
+    /// use stuff::StuffedPtr;
+    ///
+    /// let mut array = [1_u16, 2, 3];
+    /// let stuffed: StuffedPtr<u16, ()> = StuffedPtr::new_ptr(array.as_mut_ptr().wrapping_add(1));
+    /// // go back to the first element
+    /// let stuffed = stuffed.map_addr(|addr| addr - 2);
+    ///
+    /// assert_eq!(unsafe { *stuffed.unwrap_ptr() }, 1);
+    /// ```
+    pub fn map_addr(self, f: impl FnOnce(usize) -> usize) -> Self {
+        match self.ptr() {
+            Some(ptr) => Self::new_ptr(Strict::map_addr(ptr, f)),
+            None => self,
+        }
+    }
+
+    /// Create a new `StuffedPtr` with the provenance of the pointer but the address `addr`, or
+    /// the same `other` data if it contains `other` data. See [`sptr::Strict::with_addr`].
+    ///
+    /// This is [`StuffedPtr::map_addr`] with a fixed address.
+    pub fn with_addr(&self, addr: usize) -> Self {
+        self.map_addr(|_| addr)
+    }
+
+    /// Get the pointer data as a `*const T`, or `None` if it contains `other` data
+    pub fn as_ptr(&self) -> Option<*const T> {
+        self.ptr().map(|ptr| ptr as *const T)
+    }
+
+    /// Get a reference to the pointee, or `None` if it contains `other` data or a null pointer
+    ///
+    /// # Safety
+    /// A non-null pointer must be valid for reads, properly aligned and point to an initialized `T`
+    /// that isn't mutated for as long as the reference is alive, see [`pointer::as_ref`] for
+    /// details.
+    ///
+    /// [`pointer::as_ref`]: https://doc.rust-lang.org/std/primitive.pointer.html#method.as_ref
+    pub unsafe fn as_ref_unchecked(&self) -> Option<&T> {
+        self.ptr().and_then(|ptr| ptr.as_ref())
+    }
+
     /// Get `other` data from this, or `None` if it contains pointer data
+    #[inline]
     pub fn other(&self) -> Option<S::Other> {
-        let data = self.addr();
+        let data = self.stuffed_bits();
         S::extract(data).other()
     }
 
+    /// Returns `true` if this contains a pointer
+    #[inline]
+    pub fn is_ptr(&self) -> bool {
+        !self.is_other()
+    }
+
+    /// Returns `true` if this contains `other` data
+    #[inline]
+    pub fn is_other(&self) -> bool {
+        S::is_other(self.stuffed_bits())
+    }
+
+    /// Returns `true` if both contain a pointer with the same address, like [`core::ptr::eq`].
+    ///
+    /// Unlike the `PartialEq` impl, this never compares `other` data, so it returns `false` if
+    /// either of them contains `other` data.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (self.ptr(), other.ptr()) {
+            (Some(a), Some(b)) => core::ptr::eq(a, b),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if this contains a null pointer, and `false` if it contains `other` data.
+    ///
+    /// This depends on the strategy: with `()`, everything is a pointer, so `other` data is a
+    /// null pointer as well, but with [`NullIsNone`](`strategies::NullIsNone`), a null pointer is
+    /// turned into `other` data, so this is always `false`.
+    pub fn is_null(&self) -> bool {
+        self.ptr().map_or(false, |ptr| ptr.is_null())
+    }
+
+    /// Returns `true` if this contains `other` data equal to `other`.
+    ///
+    /// This is the counterpart to the `PartialEq<*mut T>` impl. It can't be a `PartialEq` impl
+    /// itself, because that would overlap with comparing two `StuffedPtr`s.
+    pub fn eq_other(&self, other: &S::Other) -> bool
+    where
+        S::Other: PartialEq,
+    {
+        self.other() == Some(*other)
+    }
+
+    /// Get the pointer data, or `default` if it contains `other` data
+    pub fn ptr_or(&self, default: *mut T) -> *mut T {
+        self.ptr().unwrap_or(default)
+    }
+
+    /// Get the pointer data, or the result of `f` if it contains `other` data
+    pub fn ptr_or_else(&self, f: impl FnOnce() -> *mut T) -> *mut T {
+        self.ptr().unwrap_or_else(f)
+    }
+
+    /// Get the pointer data, panicking if it contains `other` data
+    pub fn unwrap_ptr(&self) -> *mut T {
+        self.expect_ptr("called `unwrap_ptr` on a `StuffedPtr` containing other data")
+    }
+
+    /// Get the pointer data, panicking with `msg` if it contains `other` data
+    pub fn expect_ptr(&self, msg: &str) -> *mut T {
+        match self.ptr() {
+            Some(ptr) => ptr,
+            None => panic!("{}", msg),
+        }
+    }
+
+    /// Get the `other` data, panicking if it contains a pointer
+    pub fn unwrap_other(&self) -> S::Other {
+        self.expect_other("called `unwrap_other` on a `StuffedPtr` containing a pointer")
+    }
+
+    /// Get the `other` data, panicking with `msg` if it contains a pointer
+    pub fn expect_other(&self, msg: &str) -> S::Other {
+        match self.other() {
+            Some(other) => other,
+            None => panic!("{}", msg),
+        }
+    }
+
     /// Get out the unstuffed enum representation
+    #[inline]
     pub fn unstuff(&self) -> Unstuffed<*mut T, S::Other> {
         let (provenance, stored) = B::get_ptr(self.0);
         let either = S::extract(stored);
         either.map_ptr(|addr| Strict::with_addr(provenance as *mut T, addr))
     }
 
-    fn addr(&self) -> B {
+    /// Get the `other` data out, or get `self` back if it contains a pointer.
+    ///
+    /// This is what a `TryFrom<StuffedPtr<T, S, B>>` impl for `S::Other` would do, but such an
+    /// impl would conflict with the blanket `TryFrom` impl of `core` for types that implement
+    /// `From`.
+    pub fn try_into_other(self) -> Result<S::Other, Self> {
+        self.filter_other(|_| true)
+    }
+
+    /// Get the `other` data out if it matches the predicate, or get `self` back if it contains a
+    /// pointer or the predicate doesn't match.
+    pub fn filter_other(self, pred: impl FnOnce(&S::Other) -> bool) -> Result<S::Other, Self> {
+        match self.other() {
+            Some(other) if pred(&other) => Ok(other),
+            _ => Err(self),
+        }
+    }
+
+    /// Cast the pointee type to `U`. The stored bits and provenance are not changed.
+    pub fn cast<U>(self) -> StuffedPtr<U, S, B> {
+        StuffedPtr(self.0, PhantomData)
+    }
+
+    /// Convert the `StuffedPtr` to another backend `B2`.
+    ///
+    /// The value is unstuffed and stuffed into the new backend again, so the strategy must
+    /// support both backends with the same `other` type.
+    pub fn into_backend<B2>(self) -> StuffedPtr<T, S, B2>
+    where
+        S: StuffingStrategy<B2, Other = <S as StuffingStrategy<B>>::Other>,
+        B2: Backend,
+    {
+        match self.unstuff() {
+            Unstuffed::Ptr(ptr) => StuffedPtr::new_ptr(ptr),
+            Unstuffed::Other(other) => StuffedPtr::new_other(other),
+        }
+    }
+
+    /// Get a copy of the underlying storage of the backend.
+    ///
+    /// This is mostly useful for building abstractions on top of `StuffedPtr`. The value can be
+    /// turned back into a `StuffedPtr` using [`StuffedPtr::from_raw_stored`].
+    pub fn raw_stored(&self) -> B::Stored {
+        self.0
+    }
+
+    /// Turn the `StuffedPtr` into the underlying storage of the backend.
+    ///
+    /// Since `StuffedPtr` is `#[repr(transparent)]` to a `B::Stored`, this can be used to pass
+    /// it over an FFI boundary. The value can be turned back into a `StuffedPtr` using
+    /// [`StuffedPtr::from_raw_stored`].
+    pub fn into_raw_stored(self) -> B::Stored {
+        self.0
+    }
+
+    #[cfg(feature = "const-fn")]
+    from_raw_stored!(const);
+    #[cfg(not(feature = "const-fn"))]
+    from_raw_stored!();
+
+    /// Replace the value with `new` if it's currently equal to `expected`, returning the current
+    /// value otherwise.
+    ///
+    /// This mirrors the semantics of atomic compare-and-swap operations for single-threaded
+    /// use, without any spurious failures. The comparison is done on the stuffed bits, not
+    /// through [`PartialEq`], so pointers with the same address but different provenance compare
+    /// as equal.
+    pub fn compare_exchange(&mut self, expected: Self, new: Self) -> Result<(), Self>
+    where
+        B: PartialEq,
+    {
+        if self.stuffed_bits() == expected.stuffed_bits() {
+            *self = new;
+            Ok(())
+        } else {
+            Err(*self)
+        }
+    }
+
+    /// Get the raw stuffed integer, as seen by the [`StuffingStrategy`].
+    ///
+    /// This is useful for debugging and testing strategies. The returned integer doesn't carry
+    /// any provenance, so it *must not* be used to create a pointer. Use [`StuffedPtr::ptr`] for
+    /// that instead.
+    #[inline]
+    pub fn stuffed_bits(&self) -> B {
         B::get_int(self.0)
     }
 }
 
+/// Creates a `StuffedPtr` containing the pointer.
+///
+/// There's no equivalent impl for `other` data, since it would conflict with `impl<T> From<T> for T`
+/// (the `other` type could be the `StuffedPtr` itself). Use [`StuffedPtr::new_other`] for that.
+impl<T, S, B> From<*mut T> for StuffedPtr<T, S, B>
+where
+    S: StuffingStrategy<B>,
+    B: Backend,
+{
+    fn from(ptr: *mut T) -> Self {
+        Self::new_ptr(ptr)
+    }
+}
+
 impl<T, S, B> Debug for StuffedPtr<T, S, B>
 where
     S: StuffingStrategy<B>,
@@ -181,6 +643,41 @@ where
     }
 }
 
+impl<T, S, B> Pointer for StuffedPtr<T, S, B>
+where
+    S: StuffingStrategy<B>,
+    B: Backend,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self.ptr() {
+            Some(ptr) => Pointer::fmt(&ptr, f),
+            None => f.write_str("<other>"),
+        }
+    }
+}
+
+/// Formats the raw stuffed integer, see [`StuffedPtr::stuffed_bits`].
+impl<T, S, B> Binary for StuffedPtr<T, S, B>
+where
+    S: StuffingStrategy<B>,
+    B: Backend + Binary,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Binary::fmt(&self.stuffed_bits(), f)
+    }
+}
+
+/// Formats the raw stuffed integer, see [`StuffedPtr::stuffed_bits`].
+impl<T, S, B> LowerHex for StuffedPtr<T, S, B>
+where
+    S: StuffingStrategy<B>,
+    B: Backend + LowerHex,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        LowerHex::fmt(&self.stuffed_bits(), f)
+    }
+}
+
 impl<T, S, B> Clone for StuffedPtr<T, S, B>
 where
     S: StuffingStrategy<B>,
@@ -198,6 +695,30 @@ where
 {
 }
 
+// SAFETY: Like `Box<T>`, the pointee can be accessed from the thread the `StuffedPtr` was sent to,
+//         so it must be `Send`. The pointer is only handed out as a raw pointer, so dereferencing
+//         it is already unsafe, and it's the users responsibility to not send dangling pointers.
+//         The `other` data can be copied out on the other thread, so it must be `Send` as well.
+unsafe impl<T, S, B> Send for StuffedPtr<T, S, B>
+where
+    T: Send,
+    S: StuffingStrategy<B>,
+    S::Other: Send,
+    B: Backend,
+{
+}
+
+// SAFETY: Like `&T`, a shared `StuffedPtr` gives access to the pointee and the `other` data from
+//         multiple threads, so both must be `Sync`. See above for the pointer.
+unsafe impl<T, S, B> Sync for StuffedPtr<T, S, B>
+where
+    T: Sync,
+    S: StuffingStrategy<B>,
+    S::Other: Sync,
+    B: Backend,
+{
+}
+
 impl<T, S, B> PartialEq for StuffedPtr<T, S, B>
 where
     S: StuffingStrategy<B>,
@@ -213,6 +734,17 @@ where
     }
 }
 
+/// Compares equal if the `StuffedPtr` contains exactly this pointer.
+impl<T, S, B> PartialEq<*mut T> for StuffedPtr<T, S, B>
+where
+    S: StuffingStrategy<B>,
+    B: Backend,
+{
+    fn eq(&self, other: &*mut T) -> bool {
+        self.ptr().map_or(false, |ptr| core::ptr::eq(ptr, *other))
+    }
+}
+
 impl<T, S, B> Eq for StuffedPtr<T, S, B>
 where
     S: StuffingStrategy<B>,
@@ -221,6 +753,40 @@ where
 {
 }
 
+/// Pointers are ordered by their address and always come before `other` data.
+impl<T, S, B> PartialOrd for StuffedPtr<T, S, B>
+where
+    S: StuffingStrategy<B>,
+    S::Other: PartialOrd,
+    B: Backend,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self.unstuff(), other.unstuff()) {
+            (Unstuffed::Ptr(a), Unstuffed::Ptr(b)) => Some(a.cmp(&b)),
+            (Unstuffed::Other(a), Unstuffed::Other(b)) => a.partial_cmp(&b),
+            (Unstuffed::Ptr(_), Unstuffed::Other(_)) => Some(Ordering::Less),
+            (Unstuffed::Other(_), Unstuffed::Ptr(_)) => Some(Ordering::Greater),
+        }
+    }
+}
+
+/// Pointers are ordered by their address and always come before `other` data.
+impl<T, S, B> Ord for StuffedPtr<T, S, B>
+where
+    S: StuffingStrategy<B>,
+    S::Other: Ord,
+    B: Backend,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.unstuff(), other.unstuff()) {
+            (Unstuffed::Ptr(a), Unstuffed::Ptr(b)) => a.cmp(&b),
+            (Unstuffed::Other(a), Unstuffed::Other(b)) => a.cmp(&b),
+            (Unstuffed::Ptr(_), Unstuffed::Other(_)) => Ordering::Less,
+            (Unstuffed::Other(_), Unstuffed::Ptr(_)) => Ordering::Greater,
+        }
+    }
+}
+
 impl<T, S, B> Hash for StuffedPtr<T, S, B>
 where
     S: StuffingStrategy<B>,
@@ -239,9 +805,41 @@ where
     }
 }
 
+/// Consume an iterator of stuffed values, folding the `other` data with `f` and the pointers
+/// with `on_ptr`.
+///
+/// Each value is unstuffed exactly once, so this is a convenient way to aggregate a stream of
+/// mixed values.
+///
+/// ```
+/// use stuff::{fold_others, StuffedPtr};
+///
+/// let mut a = 1;
+/// let values: [StuffedPtr<i32, ()>; 2] = [StuffedPtr::new_ptr(&mut a), StuffedPtr::new_ptr(&mut a)];
+///
+/// let ptrs = fold_others(values.iter().copied(), 0, |acc, ()| acc, |acc, _| acc + 1);
+/// assert_eq!(ptrs, 2);
+/// ```
+pub fn fold_others<T, S, B, Acc>(
+    iter: impl Iterator<Item = StuffedPtr<T, S, B>>,
+    init: Acc,
+    mut f: impl FnMut(Acc, S::Other) -> Acc,
+    mut on_ptr: impl FnMut(Acc, *mut T) -> Acc,
+) -> Acc
+where
+    S: StuffingStrategy<B>,
+    B: Backend,
+{
+    iter.fold(init, |acc, stuffed| match stuffed.unstuff() {
+        Unstuffed::Ptr(ptr) => on_ptr(acc, ptr),
+        Unstuffed::Other(other) => f(acc, other),
+    })
+}
+
 mod either {
     /// The enum representation of a `StuffedPtr`
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[must_use]
     pub enum Unstuffed<P, O> {
         /// The pointer or pointer address
         Ptr(P),
@@ -257,6 +855,37 @@ mod either {
                 Unstuffed::Other(_) => None,
             }
         }
+    }
+
+    impl<P, O> Unstuffed<P, O> {
+        /// Returns `true` if it's the pointer
+        pub fn is_ptr(&self) -> bool {
+            match *self {
+                Unstuffed::Ptr(_) => true,
+                Unstuffed::Other(_) => false,
+            }
+        }
+
+        /// Returns `true` if it's the other type
+        pub fn is_other(&self) -> bool {
+            !self.is_ptr()
+        }
+
+        /// Converts from `&Unstuffed<P, O>` to `Unstuffed<&P, &O>`
+        pub fn as_ref(&self) -> Unstuffed<&P, &O> {
+            match *self {
+                Unstuffed::Ptr(ref ptr) => Unstuffed::Ptr(ptr),
+                Unstuffed::Other(ref other) => Unstuffed::Other(other),
+            }
+        }
+
+        /// Converts from `&mut Unstuffed<P, O>` to `Unstuffed<&mut P, &mut O>`
+        pub fn as_mut(&mut self) -> Unstuffed<&mut P, &mut O> {
+            match *self {
+                Unstuffed::Ptr(ref mut ptr) => Unstuffed::Ptr(ptr),
+                Unstuffed::Other(ref mut other) => Unstuffed::Other(other),
+            }
+        }
 
         /// Get the other type, or `None` if it's the pointer
         pub fn other(self) -> Option<O> {
@@ -266,6 +895,132 @@ mod either {
             }
         }
 
+        /// Get the pointer, or `None` if it's the other
+        ///
+        /// Unlike [`Unstuffed::ptr`], this consumes `self` and therefore doesn't require `P: Copy`.
+        pub fn ok_ptr(self) -> Option<P> {
+            match self {
+                Unstuffed::Ptr(ptr) => Some(ptr),
+                Unstuffed::Other(_) => None,
+            }
+        }
+
+        /// Get the other type, or `None` if it's the pointer
+        pub fn ok_other(self) -> Option<O> {
+            self.other()
+        }
+
+        /// Converts the pointer into `Ok` and the other type into `Err`
+        ///
+        /// ```
+        /// use stuff::Unstuffed;
+        ///
+        /// let ptr: Unstuffed<u8, &str> = Unstuffed::Ptr(1);
+        /// assert_eq!(ptr.into_result(), Ok(1));
+        ///
+        /// let other: Unstuffed<u8, &str> = Unstuffed::Other("other");
+        /// assert_eq!(other.into_result(), Err("other"));
+        /// ```
+        pub fn into_result(self) -> Result<P, O> {
+            match self {
+                Unstuffed::Ptr(ptr) => Ok(ptr),
+                Unstuffed::Other(other) => Err(other),
+            }
+        }
+
+        /// Converts the other type into `Ok` and the pointer into `Err`
+        ///
+        /// ```
+        /// use stuff::Unstuffed;
+        ///
+        /// let ptr: Unstuffed<u8, &str> = Unstuffed::Ptr(1);
+        /// assert_eq!(ptr.into_other_result(), Err(1));
+        ///
+        /// let other: Unstuffed<u8, &str> = Unstuffed::Other("other");
+        /// assert_eq!(other.into_other_result(), Ok("other"));
+        /// ```
+        pub fn into_other_result(self) -> Result<O, P> {
+            match self {
+                Unstuffed::Ptr(ptr) => Err(ptr),
+                Unstuffed::Other(other) => Ok(other),
+            }
+        }
+
+        /// Swaps the two variants, turning the pointer into the other type and the other type
+        /// into the pointer
+        ///
+        /// ```
+        /// use stuff::Unstuffed;
+        ///
+        /// let ptr: Unstuffed<u8, &str> = Unstuffed::Ptr(1);
+        /// assert_eq!(ptr.flip(), Unstuffed::Other(1));
+        /// ```
+        pub fn flip(self) -> Unstuffed<O, P> {
+            match self {
+                Unstuffed::Ptr(ptr) => Unstuffed::Other(ptr),
+                Unstuffed::Other(other) => Unstuffed::Ptr(other),
+            }
+        }
+
+        /// Returns an iterator over the other type, yielding one item if it's other and none if
+        /// it's a pointer
+        ///
+        /// This is useful for collecting all `other` data out of many `StuffedPtr`s.
+        ///
+        /// ```
+        /// use stuff::{strategies::NanBoxStrategy, StuffedPtr};
+        ///
+        /// type Value = StuffedPtr<u32, NanBoxStrategy, u64>;
+        ///
+        /// let mut object = 5;
+        /// let stack: Vec<Value> = vec![
+        ///     StuffedPtr::new_other(1.5),
+        ///     StuffedPtr::new_ptr(&mut object),
+        ///     StuffedPtr::new_other(-3.0),
+        /// ];
+        ///
+        /// let floats: Vec<f64> = stack
+        ///     .iter()
+        ///     .flat_map(|value| value.unstuff().other_iter())
+        ///     .collect();
+        /// assert_eq!(floats, [1.5, -3.0]);
+        /// ```
+        pub fn other_iter(self) -> core::option::IntoIter<O> {
+            self.ok_other().into_iter()
+        }
+
+        /// Returns an iterator over the pointer, yielding one item if it's a pointer and none if
+        /// it's other
+        pub fn ptr_iter(self) -> core::option::IntoIter<P> {
+            self.ok_ptr().into_iter()
+        }
+
+        /// Get the pointer, panicking if it's the other
+        pub fn unwrap_ptr(self) -> P {
+            self.expect_ptr("called `unwrap_ptr` on an `Unstuffed::Other`")
+        }
+
+        /// Get the pointer, panicking with `msg` if it's the other
+        pub fn expect_ptr(self, msg: &str) -> P {
+            match self {
+                Unstuffed::Ptr(ptr) => ptr,
+                Unstuffed::Other(_) => panic!("{}", msg),
+            }
+        }
+
+        /// Get the other type, panicking if it's the pointer
+        pub fn unwrap_other(self) -> O {
+            self.expect_other("called `unwrap_other` on an `Unstuffed::Ptr`")
+        }
+
+        /// Get the other type, panicking with `msg` if it's the pointer
+        pub fn expect_other(self, msg: &str) -> O {
+            match self {
+                Unstuffed::Ptr(_) => panic!("{}", msg),
+                Unstuffed::Other(other) => other,
+            }
+        }
+
         /// Maps the pointer type if it's a pointer, or does nothing if it's other
         pub fn map_ptr<U>(self, f: impl FnOnce(P) -> U) -> Unstuffed<U, O> {
             match self {
@@ -273,6 +1028,38 @@ mod either {
                 Unstuffed::Other(other) => Unstuffed::Other(other),
             }
         }
+
+        /// Maps the other type if it's other, or does nothing if it's a pointer
+        pub fn map_other<U>(self, f: impl FnOnce(O) -> U) -> Unstuffed<P, U> {
+            match self {
+                Unstuffed::Ptr(ptr) => Unstuffed::Ptr(ptr),
+                Unstuffed::Other(other) => Unstuffed::Other(f(other)),
+            }
+        }
+
+        /// Maps the pointer type with `f` if it's a pointer, or the other type with `g` if it's
+        /// other
+        pub fn map<P2, O2>(
+            self,
+            f: impl FnOnce(P) -> P2,
+            g: impl FnOnce(O) -> O2,
+        ) -> Unstuffed<P2, O2> {
+            match self {
+                Unstuffed::Ptr(ptr) => Unstuffed::Ptr(f(ptr)),
+                Unstuffed::Other(other) => Unstuffed::Other(g(other)),
+            }
+        }
+    }
+
+    /// `Ok` becomes the pointer and `Err` becomes the other type, the inverse of
+    /// [`Unstuffed::into_result`]
+    impl<P, O> From<Result<P, O>> for Unstuffed<P, O> {
+        fn from(result: Result<P, O>) -> Self {
+            match result {
+                Ok(ptr) => Unstuffed::Ptr(ptr),
+                Err(other) => Unstuffed::Other(other),
+            }
+        }
     }
 }
 
@@ -280,13 +1067,15 @@ mod either {
 mod tests {
     #![allow(non_snake_case, clippy::undocumented_unsafe_blocks)]
 
-    use std::{boxed::Box, format, println};
+    use std::{boxed::Box, format, println, vec::Vec};
 
     use paste::paste;
 
     use crate::{
-        strategy::test_strategies::{EmptyInMax, HasDebug},
-        Backend, StuffedPtr, StuffingStrategy,
+        fold_others,
+        strategies::NullIsNone,
+        strategy::test_strategies::{EmptyInMax, HasDebug, LowBitU16},
+        Backend, StuffedPtr, StuffingStrategy, Unstuffed,
     };
 
     fn from_box<T, S, B>(boxed: Box<T>) -> StuffedPtr<T, S, B>
@@ -318,6 +1107,74 @@ mod tests {
                     assert!(matches!(stuffed_ptr.other(), Some(EmptyInMax)));
                 }
 
+                #[test]
+                fn [<set_ptr__ $backend>]() {
+                    let mut a = 1;
+                    let mut b = 2;
+                    let mut stuffed_ptr: StuffedPtr<i32, EmptyInMax, $backend> = StuffedPtr::new_other(EmptyInMax);
+
+                    stuffed_ptr.set_ptr(&mut a);
+                    assert_eq!(unsafe { *stuffed_ptr.ptr().unwrap() }, 1);
+
+                    stuffed_ptr.set_ptr(&mut b);
+                    assert_eq!(unsafe { *stuffed_ptr.ptr().unwrap() }, 2);
+                }
+
+                #[test]
+                fn [<try_new_ptr__ $backend>]() {
+                    let mut a = 1;
+                    let stuffed_ptr: StuffedPtr<i32, EmptyInMax, $backend> = StuffedPtr::try_new_ptr(&mut a).unwrap();
+                    assert_eq!(unsafe { *stuffed_ptr.ptr().unwrap() }, 1);
+                }
+
+                #[test]
+                fn [<deref_reconstructed__ $backend>]() {
+                    // the pointer is reconstructed from the backend, this checks that it keeps the
+                    // provenance, run under miri with `-Zmiri-strict-provenance`
+                    let mut array = Box::new([1_u64, 2, 3, 4]);
+                    for i in 0..array.len() {
+                        let stuffed_ptr: StuffedPtr<u64, EmptyInMax, $backend> = StuffedPtr::new_ptr(&mut array[i]);
+                        let stuffed_ptr = StuffedPtr::<u64, EmptyInMax, $backend>::new_ptr(stuffed_ptr.unwrap_ptr());
+                        unsafe { *stuffed_ptr.unwrap_ptr() *= 10 };
+                    }
+                    assert_eq!(*array, [10, 20, 30, 40]);
+                }
+
+                #[test]
+                fn [<swap__ $backend>]() {
+                    let mut a = 1;
+                    let mut ptr: StuffedPtr<i32, EmptyInMax, $backend> = StuffedPtr::new_ptr(&mut a);
+                    let mut other: StuffedPtr<i32, EmptyInMax, $backend> = StuffedPtr::new_other(EmptyInMax);
+
+                    ptr.swap(&mut other);
+                    assert_eq!(ptr.other(), Some(EmptyInMax));
+                    assert_eq!(unsafe { *other.ptr().unwrap() }, 1);
+                }
+
+                #[test]
+                fn [<cast__ $backend>]() {
+                    let mut a = 1;
+                    let erased: StuffedPtr<(), EmptyInMax, $backend> = StuffedPtr::new_ptr(&mut a as *mut i32 as *mut ());
+                    let stuffed_ptr: StuffedPtr<i32, EmptyInMax, $backend> = erased.cast();
+                    assert_eq!(unsafe { *stuffed_ptr.ptr().unwrap() }, 1);
+
+                    let erased: StuffedPtr<(), EmptyInMax, $backend> = StuffedPtr::new_other(EmptyInMax);
+                    let stuffed_ptr: StuffedPtr<i32, EmptyInMax, $backend> = erased.cast();
+                    assert_eq!(stuffed_ptr.other(), Some(EmptyInMax));
+                }
+
+                #[test]
+                fn [<is_ptr_is_other__ $backend>]() {
+                    let mut unit = ();
+                    let stuffed_ptr: StuffedPtr<(), EmptyInMax, $backend> = StuffedPtr::new_ptr(&mut unit);
+                    assert!(stuffed_ptr.is_ptr());
+                    assert!(!stuffed_ptr.is_other());
+
+                    let stuffed_ptr: StuffedPtr<(), EmptyInMax, $backend> = StuffedPtr::new_other(EmptyInMax);
+                    assert!(!stuffed_ptr.is_ptr());
+                    assert!(stuffed_ptr.is_other());
+                }
+
                 #[test]
                 fn [<debug__ $backend>]() {
                     let boxed = Box::new(1);
@@ -336,6 +1193,16 @@ mod tests {
                     );
                 }
 
+                #[test]
+                fn [<pointer_fmt__ $backend>]() {
+                    let mut unit = ();
+                    let stuffed_ptr: StuffedPtr<(), EmptyInMax, $backend> = StuffedPtr::new_ptr(&mut unit);
+                    assert_eq!(format!("{stuffed_ptr:p}"), format!("{:p}", &mut unit as *mut ()));
+
+                    let stuffed_ptr: StuffedPtr<(), EmptyInMax, $backend> = StuffedPtr::new_other(EmptyInMax);
+                    assert_eq!(format!("{stuffed_ptr:p}"), "<other>");
+                }
+
                 #[test]
                 #[allow(clippy::redundant_clone)]
                 fn [<clone__ $backend>]() {
@@ -363,11 +1230,611 @@ mod tests {
                     assert_ne!(stuffed_ptr1, stuffed_ptr2);
                 }
 
+                #[test]
+                fn [<raw_stored__ $backend>]() {
+                    let mut unit = ();
+                    let stuffed_ptr: StuffedPtr<(), EmptyInMax, $backend> = StuffedPtr::new_ptr(&mut unit);
+                    let stored = stuffed_ptr.raw_stored();
+                    let stuffed_ptr2: StuffedPtr<(), EmptyInMax, $backend> = unsafe { StuffedPtr::from_raw_stored(stored) };
+                    assert_eq!(stuffed_ptr, stuffed_ptr2);
+                    assert_eq!(stuffed_ptr2.ptr(), Some(&mut unit as *mut ()));
+
+                    let stuffed_ptr: StuffedPtr<(), EmptyInMax, $backend> = StuffedPtr::new_other(EmptyInMax);
+                    let stored = stuffed_ptr.into_raw_stored();
+                    let stuffed_ptr2: StuffedPtr<(), EmptyInMax, $backend> = unsafe { StuffedPtr::from_raw_stored(stored) };
+                    assert_eq!(stuffed_ptr2.other(), Some(EmptyInMax));
+                }
+
+                #[test]
+                fn [<compare_exchange__ $backend>]() {
+                    let mut unit = ();
+                    let ptr: StuffedPtr<(), EmptyInMax, $backend> = StuffedPtr::new_ptr(&mut unit);
+                    let other: StuffedPtr<(), EmptyInMax, $backend> = StuffedPtr::new_other(EmptyInMax);
+
+                    let mut slot = ptr;
+                    assert_eq!(slot.compare_exchange(ptr, other), Ok(()));
+                    assert_eq!(slot, other);
+
+                    assert_eq!(slot.compare_exchange(ptr, ptr), Err(other));
+                    assert_eq!(slot, other);
+
+                    assert_eq!(slot.compare_exchange(other, ptr), Ok(()));
+                    assert_eq!(slot, ptr);
+                }
             }
         };
     }
 
+    #[test]
+    fn fold_others_sum() {
+        let mut a = 1_u64;
+        let mut b = 2_u64;
+        let values: Vec<StuffedPtr<u64, LowBitU16>> = [
+            StuffedPtr::new_other(1),
+            StuffedPtr::new_ptr(&mut a),
+            StuffedPtr::new_other(20),
+            StuffedPtr::new_ptr(&mut b),
+            StuffedPtr::new_other(300),
+        ]
+        .to_vec();
+
+        let (sum, ptrs) = fold_others(
+            values.into_iter(),
+            (0, 0),
+            |(sum, ptrs), other| (sum + u32::from(other), ptrs),
+            |(sum, ptrs), _| (sum, ptrs + 1),
+        );
+
+        assert_eq!(sum, 321);
+        assert_eq!(ptrs, 2);
+    }
+
+    #[test]
+    fn stuffed_bits() {
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(5);
+        assert_eq!(stuffed.stuffed_bits(), 0b1011);
+
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(core::ptr::null_mut());
+        assert_eq!(stuffed.stuffed_bits(), 0);
+    }
+
+    #[test]
+    fn fmt_stuffed_bits() {
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(5);
+        assert_eq!(format!("{stuffed:b}"), "1011");
+        assert_eq!(format!("{stuffed:#06x}"), "0x000b");
+
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(core::ptr::null_mut());
+        assert_eq!(format!("{stuffed:x}"), "0");
+    }
+
+    #[test]
+    fn null() {
+        let null: StuffedPtr<u64, ()> = StuffedPtr::null_ptr();
+        assert!(null.is_null());
+        assert!(null.is_ptr());
+        let other: StuffedPtr<u64, ()> = StuffedPtr::new_other(());
+        assert!(other.is_null());
+
+        let null: StuffedPtr<u64, NullIsNone> = StuffedPtr::null_ptr();
+        assert!(!null.is_null());
+        assert_eq!(null.other(), Some(()));
+
+        let mut a = 1_u64;
+        let ptr: StuffedPtr<u64, NullIsNone> = StuffedPtr::new_ptr(&mut a);
+        assert!(!ptr.is_null());
+        let other: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(0);
+        assert!(!other.is_null());
+    }
+
+    #[test]
+    fn from_ref_from_mut() {
+        let mut a = 1_u64;
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::from_mut(&mut a);
+        unsafe { *stuffed.unwrap_ptr() += 1 };
+        assert_eq!(a, 2);
+
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::from_ref(&a);
+        assert_eq!(unsafe { *stuffed.unwrap_ptr() }, 2);
+        assert_eq!(stuffed.ptr(), Some(&a as *const u64 as *mut u64));
+    }
+
+    // a backend that is smaller than a pointer, so `()` can't stuff all addresses
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    struct Narrow(u16);
+
+    unsafe impl Backend for Narrow {
+        type Stored = *mut ();
+
+        fn get_ptr(s: Self::Stored) -> (*mut (), Self) {
+            (s, Self::get_int(s))
+        }
+
+        fn set_ptr(provenance: *mut (), addr: Self) -> Self::Stored {
+            sptr::Strict::with_addr(provenance, usize::from(addr.0))
+        }
+
+        fn get_int(s: Self::Stored) -> Self {
+            Narrow(sptr::Strict::addr(s) as u16)
+        }
+    }
+
+    impl core::convert::TryFrom<usize> for Narrow {
+        type Error = core::num::TryFromIntError;
+
+        fn try_from(value: usize) -> Result<Self, Self::Error> {
+            u16::try_from(value).map(Narrow)
+        }
+    }
+
+    impl From<Narrow> for usize {
+        fn from(value: Narrow) -> Self {
+            usize::from(value.0)
+        }
+    }
+
+    #[test]
+    fn unit_try_new_ptr() {
+        let small = sptr::invalid_mut::<u8>(0x1000);
+        let stuffed: StuffedPtr<u8, (), Narrow> = StuffedPtr::try_new_ptr(small).unwrap();
+        assert_eq!(stuffed.ptr(), Some(small));
+
+        let big = sptr::invalid_mut::<u8>(0x1_0000);
+        assert!(StuffedPtr::<u8, (), Narrow>::try_new_ptr(big).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Address in `stuff_ptr` too big")]
+    fn unit_new_ptr_too_big() {
+        StuffedPtr::<u8, (), Narrow>::new_ptr(sptr::invalid_mut(0x1_0000));
+    }
+
+    #[test]
+    fn dangling() {
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::dangling();
+        assert!(stuffed.is_ptr());
+        assert_eq!(
+            stuffed.ptr(),
+            Some(core::ptr::NonNull::<u64>::dangling().as_ptr())
+        );
+        assert_ne!(stuffed.ptr(), Some(core::ptr::null_mut()));
+        assert_eq!(stuffed.other(), None);
+    }
+
+    #[test]
+    fn ptr_eq() {
+        let mut a = 1_u64;
+        let mut b = 1_u64;
+        let ptr_a: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(&mut a);
+        let ptr_b: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(&mut b);
+        let other: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(5);
+
+        assert!(ptr_a.ptr_eq(&ptr_a));
+        assert!(!ptr_a.ptr_eq(&ptr_b));
+        assert!(!ptr_a.ptr_eq(&other));
+        assert!(!other.ptr_eq(&ptr_a));
+        assert!(!other.ptr_eq(&other));
+    }
+
+    #[test]
+    fn eq_ptr_and_other() {
+        let mut a = 1_u64;
+        let ptr = &mut a as *mut u64;
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(ptr);
+        assert_eq!(stuffed, ptr);
+        assert_ne!(stuffed, core::ptr::null_mut());
+        assert!(!stuffed.eq_other(&0));
+
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(5);
+        assert!(stuffed.eq_other(&5));
+        assert!(!stuffed.eq_other(&6));
+        assert_ne!(stuffed, ptr);
+        assert_eq!(stuffed.other(), Some(5));
+    }
+
+    #[test]
+    fn non_null() {
+        let mut a = 1_u64;
+        let non_null = core::ptr::NonNull::from(&mut a);
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_non_null(non_null);
+        assert_eq!(stuffed.non_null(), Some(non_null));
+
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(core::ptr::null_mut());
+        assert_eq!(stuffed.non_null(), None);
+        assert_eq!(stuffed.ptr(), Some(core::ptr::null_mut()));
+
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(5);
+        assert_eq!(stuffed.non_null(), None);
+    }
+
+    #[test]
+    fn into_backend() {
+        let mut a = 1_u64;
+        let stuffed: StuffedPtr<u64, EmptyInMax, usize> = StuffedPtr::new_ptr(&mut a);
+        let wide: StuffedPtr<u64, EmptyInMax, u128> = stuffed.into_backend();
+        let stuffed: StuffedPtr<u64, EmptyInMax, usize> = wide.into_backend();
+        unsafe { *stuffed.unwrap_ptr() += 1 };
+        assert_eq!(a, 2);
+
+        let stuffed: StuffedPtr<u64, EmptyInMax, usize> = StuffedPtr::new_other(EmptyInMax);
+        let wide: StuffedPtr<u64, EmptyInMax, u128> = stuffed.into_backend();
+        assert_eq!(wide.other(), Some(EmptyInMax));
+    }
+
+    #[test]
+    fn from_ptr() {
+        let mut a = 1_u64;
+        let stuffed: StuffedPtr<u64, LowBitU16> = (&mut a as *mut u64).into();
+        assert_eq!(stuffed.ptr(), Some(&mut a as *mut u64));
+    }
+
+    #[test]
+    fn ord() {
+        let mut values = [0_u64; 2];
+        let (a, b) = values.split_at_mut(1);
+        let a: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(&mut a[0]);
+        let b: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(&mut b[0]);
+        let small: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(1);
+        let big: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(2);
+
+        assert!(a < b);
+        assert!(b < small);
+        assert!(small < big);
+        assert_eq!(a.cmp(&a), core::cmp::Ordering::Equal);
+        assert_eq!(big.partial_cmp(&big), Some(core::cmp::Ordering::Equal));
+
+        let mut sorted = [big, small, b, a];
+        sorted.sort();
+        assert_eq!(sorted, [a, b, small, big]);
+    }
+
+    #[test]
+    fn ptr_or() {
+        let mut a = 1_u64;
+        let mut b = 2_u64;
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(&mut a);
+        assert_eq!(stuffed.ptr_or(&mut b), &mut a as *mut u64);
+        assert_eq!(
+            stuffed.ptr_or_else(|| panic!("called for pointer")),
+            &mut a as *mut u64
+        );
+
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(5);
+        assert_eq!(stuffed.ptr_or(&mut b), &mut b as *mut u64);
+        assert_eq!(stuffed.ptr_or_else(|| &mut b), &mut b as *mut u64);
+    }
+
+    #[test]
+    fn unwrap_expect() {
+        let mut a = 1_u64;
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(&mut a);
+        assert_eq!(stuffed.unwrap_ptr(), &mut a as *mut u64);
+        assert_eq!(stuffed.expect_ptr("pointer"), &mut a as *mut u64);
+
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(5);
+        assert_eq!(stuffed.unwrap_other(), 5);
+        assert_eq!(stuffed.expect_other("other"), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "called `unwrap_ptr` on a `StuffedPtr` containing other data")]
+    fn unwrap_ptr_other() {
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(5);
+        stuffed.unwrap_ptr();
+    }
+
+    #[test]
+    #[should_panic(expected = "no other here")]
+    fn expect_other_ptr() {
+        let mut a = 1_u64;
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(&mut a);
+        stuffed.expect_other("no other here");
+    }
+
+    #[test]
+    fn filter_other() {
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(5);
+        assert_eq!(stuffed.filter_other(|&other| other == 5), Ok(5));
+        assert_eq!(stuffed.filter_other(|&other| other == 6), Err(stuffed));
+
+        let mut a = 1_u64;
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(&mut a);
+        assert_eq!(
+            stuffed.filter_other(|_| panic!("called for pointer")),
+            Err(stuffed)
+        );
+    }
+
+    #[test]
+    fn replace_other() {
+        let mut a = 1_u64;
+        let mut stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(&mut a);
+
+        assert_eq!(stuffed.replace_other(5), None);
+        assert_eq!(stuffed.other(), Some(5));
+
+        assert_eq!(stuffed.replace_other(6), Some(5));
+        assert_eq!(stuffed.other(), Some(6));
+    }
+
+    #[test]
+    fn take_other() {
+        let mut stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(5);
+
+        assert_eq!(stuffed.take_other(), Some(5));
+        assert_eq!(stuffed.ptr(), Some(core::ptr::null_mut()));
+        assert_eq!(stuffed.other(), None);
+
+        assert_eq!(stuffed.take_other(), None);
+        assert_eq!(stuffed.ptr(), Some(core::ptr::null_mut()));
+        assert_eq!(stuffed.other(), None);
+
+        let mut a = 1_u64;
+        let mut stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(&mut a);
+        assert_eq!(stuffed.take_other(), None);
+        assert_eq!(stuffed.ptr(), Some(&mut a as *mut u64));
+    }
+
+    #[test]
+    #[cfg(feature = "const-fn")]
+    fn const_from_raw_stored() {
+        const TABLE: [StuffedPtr<u64, LowBitU16>; 2] = unsafe {
+            [
+                StuffedPtr::from_raw_stored(sptr::invalid_mut((1 << 1) | 1)),
+                StuffedPtr::from_raw_stored(sptr::invalid_mut((2 << 1) | 1)),
+            ]
+        };
+
+        assert_eq!(TABLE[0].other(), Some(1));
+        assert_eq!(TABLE[1].other(), Some(2));
+    }
+
+    #[test]
+    fn as_ptr_as_ref() {
+        let mut a = 1_u64;
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(&mut a);
+        assert_eq!(stuffed.as_ptr(), Some(&a as *const u64));
+        assert_eq!(unsafe { stuffed.as_ref_unchecked() }, Some(&1));
+
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(5);
+        assert_eq!(stuffed.as_ptr(), None);
+        assert_eq!(unsafe { stuffed.as_ref_unchecked() }, None);
+    }
+
+    #[test]
+    fn with_addr() {
+        let mut array = [1_u64, 2, 3];
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(array.as_mut_ptr());
+        let addr = sptr::Strict::addr(stuffed.unwrap_ptr());
+        let stuffed = stuffed.with_addr(addr + 2 * core::mem::size_of::<u64>());
+        unsafe { *stuffed.unwrap_ptr() += 1 };
+        assert_eq!(array, [1, 2, 4]);
+
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(5);
+        assert_eq!(stuffed.with_addr(0x1000).other(), Some(5));
+    }
+
+    #[test]
+    fn map_addr() {
+        #[repr(align(16))]
+        struct Aligned([u8; 32]);
+
+        let mut aligned = Box::new(Aligned([0; 32]));
+        aligned.0[0] = 5;
+        let base = &mut aligned.0 as *mut [u8; 32] as *mut u8;
+
+        let stuffed: StuffedPtr<u8, ()> = StuffedPtr::new_ptr(base.wrapping_add(7));
+        let stuffed = stuffed.map_addr(|addr| addr & !0xf);
+        assert_eq!(stuffed.ptr(), Some(base));
+        assert_eq!(unsafe { *stuffed.unwrap_ptr() }, 5);
+
+        let stuffed: StuffedPtr<u8, LowBitU16> = StuffedPtr::new_other(3);
+        let mapped = stuffed.map_addr(|_| panic!("called for other"));
+        assert_eq!(mapped.raw_stored(), stuffed.raw_stored());
+    }
+
+    #[test]
+    fn try_into_other() {
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(5);
+        assert_eq!(stuffed.try_into_other(), Ok(5));
+
+        let mut a = 1_u64;
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(&mut a);
+        assert_eq!(stuffed.try_into_other(), Err(stuffed));
+
+        // the pointer can be recovered from the error, with its provenance
+        let recovered = stuffed.try_into_other().unwrap_err();
+        unsafe { *recovered.unwrap_ptr() += 1 };
+        assert_eq!(a, 2);
+    }
+
+    #[test]
+    fn send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<StuffedPtr<u64, LowBitU16>>();
+        assert_send_sync::<StuffedPtr<u64, EmptyInMax, u128>>();
+
+        let mut a = 1_u64;
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(&mut a);
+        std::thread::scope(|scope| {
+            scope.spawn(|| unsafe { *stuffed.unwrap_ptr() += 1 });
+        });
+        assert_eq!(a, 2);
+    }
+
+    #[test]
+    fn replace() {
+        let mut a = 1_u64;
+        let mut stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(&mut a);
+        let old = stuffed.replace(StuffedPtr::new_other(5));
+        assert_eq!(stuffed.other(), Some(5));
+
+        let old_other = stuffed.replace(old);
+        assert_eq!(old_other.other(), Some(5));
+        unsafe { *stuffed.unwrap_ptr() += 1 };
+        assert_eq!(a, 2);
+    }
+
+    #[test]
+    fn get_or_insert_other() {
+        let mut a = 1_u64;
+        let mut stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(&mut a);
+        assert_eq!(stuffed.get_or_insert_other(5), 5);
+        assert_eq!(stuffed.other(), Some(5));
+
+        assert_eq!(stuffed.get_or_insert_other(6), 5);
+        assert_eq!(
+            stuffed.get_or_insert_other_with(|| panic!("called for other")),
+            5
+        );
+        assert_eq!(stuffed.other(), Some(5));
+    }
+
+    #[test]
+    fn modify_other() {
+        let mut stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(5);
+        assert!(stuffed.modify_other(|other| *other += 1));
+        assert_eq!(stuffed.other(), Some(6));
+
+        let mut a = 1_u64;
+        let mut stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(&mut a);
+        assert!(!stuffed.modify_other(|_| panic!("called for pointer")));
+        assert_eq!(stuffed.ptr(), Some(&mut a as *mut u64));
+    }
+
+    #[test]
+    fn map_other_in_place() {
+        let mut stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(5);
+        stuffed.map_other_in_place(|other| other * 2);
+        assert_eq!(stuffed.other(), Some(10));
+
+        let mut a = 1_u64;
+        let mut stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(&mut a);
+        stuffed.map_other_in_place(|_| panic!("called for pointer"));
+        assert_eq!(stuffed.ptr(), Some(&mut a as *mut u64));
+    }
+
+    #[test]
+    fn unstuffed_as_ref_as_mut() {
+        // `Box` isn't `Copy`, so this also checks that no `Copy` bound is required
+        let mut unstuffed: Unstuffed<Box<u8>, u16> = Unstuffed::Ptr(Box::new(1));
+        assert_eq!(unstuffed.as_ref(), Unstuffed::Ptr(&Box::new(1)));
+        if let Unstuffed::Ptr(ptr) = unstuffed.as_mut() {
+            **ptr = 2;
+        }
+        assert_eq!(unstuffed.map_ptr(|ptr| *ptr), Unstuffed::Ptr(2));
+
+        let mut unstuffed: Unstuffed<Box<u8>, u16> = Unstuffed::Other(3);
+        if let Unstuffed::Other(other) = unstuffed.as_mut() {
+            *other += 1;
+        }
+        assert_eq!(unstuffed.as_ref(), Unstuffed::Other(&4));
+        assert_eq!(unstuffed.other(), Some(4));
+    }
+
+    #[test]
+    fn unstuffed_map() {
+        let unstuffed: Unstuffed<u8, u16> = Unstuffed::Ptr(1);
+        let mapped = unstuffed.map(|ptr| ptr + 1, |_| -> u16 { panic!("mapped other") });
+        assert_eq!(mapped, Unstuffed::Ptr(2));
+        assert_eq!(
+            unstuffed.map_other(|_| -> u16 { panic!("mapped other") }),
+            Unstuffed::Ptr(1)
+        );
+
+        let unstuffed: Unstuffed<u8, u16> = Unstuffed::Other(3);
+        let mapped = unstuffed.map(|_| -> u8 { panic!("mapped ptr") }, |other| other * 2);
+        assert_eq!(mapped, Unstuffed::Other(6));
+        assert_eq!(unstuffed.map_other(|other| other + 1), Unstuffed::Other(4));
+    }
+
+    #[test]
+    fn unstuffed_unwrap_expect() {
+        let ptr: Unstuffed<u8, u16> = Unstuffed::Ptr(1);
+        assert_eq!(ptr.unwrap_ptr(), 1);
+        assert_eq!(ptr.expect_ptr("not a pointer"), 1);
+
+        let other: Unstuffed<u8, u16> = Unstuffed::Other(2);
+        assert_eq!(other.unwrap_other(), 2);
+        assert_eq!(other.expect_other("not other"), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "called `unwrap_ptr` on an `Unstuffed::Other`")]
+    fn unstuffed_unwrap_ptr_other() {
+        let other: Unstuffed<u8, u16> = Unstuffed::Other(2);
+        other.unwrap_ptr();
+    }
+
+    #[test]
+    #[should_panic(expected = "not other")]
+    fn unstuffed_expect_other_ptr() {
+        let ptr: Unstuffed<u8, u16> = Unstuffed::Ptr(1);
+        ptr.expect_other("not other");
+    }
+
+    #[test]
+    fn unstuffed_result() {
+        let ptr: Unstuffed<Box<u8>, u16> = Unstuffed::Ptr(Box::new(1));
+        assert_eq!(ptr.ok_ptr(), Some(Box::new(1)));
+
+        let other: Unstuffed<Box<u8>, u16> = Unstuffed::Other(2);
+        assert_eq!(other.ok_other(), Some(2));
+
+        let result: Result<u8, u16> = Err(3);
+        let unstuffed = Unstuffed::from(result);
+        assert_eq!(unstuffed, Unstuffed::Other(3));
+        assert_eq!(unstuffed.into_result(), result);
+        assert_eq!(Unstuffed::from(Ok::<u8, u16>(4)).ok_ptr(), Some(4));
+    }
+
+    #[test]
+    fn unstuffed_is_ptr_is_other() {
+        let ptr: Unstuffed<Box<u8>, u16> = Unstuffed::Ptr(Box::new(1));
+        assert!(ptr.is_ptr());
+        assert!(!ptr.is_other());
+
+        let other: Unstuffed<Box<u8>, u16> = Unstuffed::Other(2);
+        assert!(!other.is_ptr());
+        assert!(other.is_other());
+    }
+
+    #[test]
+    fn unstuffed_flip() {
+        let ptr: Unstuffed<u8, u16> = Unstuffed::Ptr(1);
+        assert_eq!(ptr.flip(), Unstuffed::Other(1));
+        assert_eq!(ptr.flip().flip(), ptr);
+
+        let other: Unstuffed<u8, u16> = Unstuffed::Other(2);
+        assert_eq!(other.flip(), Unstuffed::Ptr(2));
+    }
+
+    #[test]
+    fn unstuffed_iter() {
+        let values: [Unstuffed<u8, u16>; 3] =
+            [Unstuffed::Other(1), Unstuffed::Ptr(2), Unstuffed::Other(3)];
+
+        let others: Vec<u16> = values.iter().flat_map(|v| v.other_iter()).collect();
+        assert_eq!(others, [1, 3]);
+
+        let ptrs: Vec<u8> = values.iter().flat_map(|v| v.ptr_iter()).collect();
+        assert_eq!(ptrs, [2]);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn box_round_trip() {
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::from_box(Box::new(5));
+        let boxed = unsafe { stuffed.into_box() }.unwrap();
+        assert_eq!(*boxed, 5);
+
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(1);
+        assert_eq!(unsafe { stuffed.into_box() }, None);
+    }
+
     make_tests!(u128);
     make_tests!(u64);
     make_tests!(usize);
+    #[cfg(target_pointer_width = "16")]
+    make_tests!(u32);
 }
@@ -0,0 +1,131 @@
+use core::hash::{Hash, Hasher};
+
+use crate::{Backend, StuffedPtr, StuffingStrategy};
+
+/// A marker trait for strategies where the stuffed integer is a canonical representation of the
+/// value. It allows [`RawHashed`] to hash and compare the stuffed integer directly.
+///
+/// Two values must be equal if and only if their stuffed integers are equal: pointers are equal
+/// if their addresses are, and `other` data is equal if `S::Other` considers it equal. For
+/// example, this doesn't hold for a strategy that ignores some bits in
+/// [`StuffingStrategy::extract`], or that stores floats, where `0.0 == -0.0`.
+pub trait RawHashable<B>: StuffingStrategy<B> {}
+
+impl<B> RawHashable<B> for () where (): StuffingStrategy<B> {}
+
+/// A [`StuffedPtr`] that is hashed and compared by its stuffed integer, see
+/// [`StuffedPtr::stuffed_bits`].
+///
+/// This is faster than the `Hash` impl of `StuffedPtr`, since it doesn't need to extract the
+/// value first, and it doesn't require `S::Other: Hash`. It's only available for strategies that
+/// implement [`RawHashable`], which is what makes it agree with the `PartialEq` impl of
+/// `StuffedPtr`.
+///
+/// ```
+/// use std::collections::HashSet;
+///
+/// use stuff::{strategies::SmallIntStrategy, RawHashed, StuffedPtr};
+///
+/// type Value = StuffedPtr<(), SmallIntStrategy, u64>;
+///
+/// let mut set = HashSet::new();
+/// set.insert(RawHashed(Value::new_other(1)));
+/// set.insert(RawHashed(Value::new_other(1)));
+/// set.insert(RawHashed(Value::new_other(2)));
+/// assert_eq!(set.len(), 2);
+/// ```
+#[repr(transparent)]
+pub struct RawHashed<T, S, B = usize>(pub StuffedPtr<T, S, B>)
+where
+    B: Backend;
+
+impl<T, S, B> Clone for RawHashed<T, S, B>
+where
+    S: StuffingStrategy<B>,
+    B: Backend,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, S, B> Copy for RawHashed<T, S, B>
+where
+    S: StuffingStrategy<B>,
+    B: Backend,
+{
+}
+
+impl<T, S, B> PartialEq for RawHashed<T, S, B>
+where
+    S: RawHashable<B>,
+    B: Backend + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0.stuffed_bits() == other.0.stuffed_bits()
+    }
+}
+
+impl<T, S, B> Eq for RawHashed<T, S, B>
+where
+    S: RawHashable<B>,
+    B: Backend + Eq,
+{
+}
+
+impl<T, S, B> Hash for RawHashed<T, S, B>
+where
+    S: RawHashable<B>,
+    B: Backend + Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.stuffed_bits().hash(state);
+    }
+}
+
+impl<T, S, B> From<StuffedPtr<T, S, B>> for RawHashed<T, S, B>
+where
+    B: Backend,
+{
+    fn from(stuffed: StuffedPtr<T, S, B>) -> Self {
+        RawHashed(stuffed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::hash::{Hash, Hasher};
+    use std::collections::hash_map::DefaultHasher;
+
+    use super::RawHashed;
+    use crate::{strategies::SmallIntStrategy, StuffedPtr};
+
+    type Value = StuffedPtr<u64, SmallIntStrategy, u64>;
+
+    fn hash(value: &impl Hash) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn agrees_with_eq() {
+        let mut a = 1_u64;
+        let mut b = 1_u64;
+        let values: [Value; 4] = [
+            StuffedPtr::new_ptr(&mut a),
+            StuffedPtr::new_ptr(&mut b),
+            StuffedPtr::new_other(1),
+            StuffedPtr::new_other(-1),
+        ];
+
+        for x in &values {
+            for y in &values {
+                assert_eq!(RawHashed(*x) == RawHashed(*y), x == y);
+                if x == y {
+                    assert_eq!(hash(&RawHashed(*x)), hash(&RawHashed(*y)));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use serde::{de::Deserializer, ser::Error, Deserialize, Serialize, Serializer};
+
+use crate::{Backend, StuffedPtr, StuffingStrategy, Unstuffed};
+
+/// Only `other` data can be serialized, it's serialized as the `other` type itself.
+///
+/// **Pointers can't be serialized**, trying to do so returns an error. Their address is meaningless
+/// outside of the current process, and their provenance can't be serialized at all, so a pointer
+/// created from deserialized data could never be used to access memory.
+///
+/// Only available with the `serde` feature.
+impl<T, S, B> Serialize for StuffedPtr<T, S, B>
+where
+    S: StuffingStrategy<B>,
+    S::Other: Serialize,
+    B: Backend,
+{
+    fn serialize<Ser>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error>
+    where
+        Ser: Serializer,
+    {
+        match self.unstuff() {
+            Unstuffed::Ptr(_) => Err(Ser::Error::custom(
+                "a `StuffedPtr` containing a pointer can't be serialized",
+            )),
+            Unstuffed::Other(other) => other.serialize(serializer),
+        }
+    }
+}
+
+/// Deserializes the `other` type and always creates a `StuffedPtr` containing `other` data.
+///
+/// Only available with the `serde` feature.
+impl<'de, T, S, B> Deserialize<'de> for StuffedPtr<T, S, B>
+where
+    S: StuffingStrategy<B>,
+    S::Other: Deserialize<'de>,
+    B: Backend,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        S::Other::deserialize(deserializer).map(StuffedPtr::new_other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_test::{assert_de_tokens, assert_ser_tokens, assert_ser_tokens_error, Token};
+
+    use crate::{strategy::test_strategies::LowBitU16, StuffedPtr};
+
+    #[test]
+    fn other() {
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_other(5);
+        assert_ser_tokens(&stuffed, &[Token::U16(5)]);
+        assert_de_tokens(&stuffed, &[Token::U16(5)]);
+    }
+
+    #[test]
+    fn ptr() {
+        let mut a = 1_u64;
+        let stuffed: StuffedPtr<u64, LowBitU16> = StuffedPtr::new_ptr(&mut a);
+        assert_ser_tokens_error(
+            &stuffed,
+            &[],
+            "a `StuffedPtr` containing a pointer can't be serialized",
+        );
+    }
+}
@@ -0,0 +1,45 @@
+//! Pre-defined [`StuffingStrategy`](`crate::StuffingStrategy`) implementations for common use cases.
+//!
+//! These are ready to be used as the `S` parameter of [`StuffedPtr`](`crate::StuffedPtr`), but
+//! also serve as examples for implementing your own strategies. Helpers like [`free_low_bits`]
+//! are useful for that too.
+//!
+//! The simplest strategy, `()`, is implemented on the unit type directly and is therefore not part
+//! of this module. It can only store pointers, which are stored as their plain address, and its
+//! `other` data `()` is stuffed as `0`, so it's indistinguishable from a null pointer.
+
+mod align;
+// const generics are only parsed in a separate file, to keep the MSRV
+#[cfg(feature = "const-generics")]
+mod align_low_bits;
+#[cfg(target_pointer_width = "64")]
+mod brads;
+mod char_immediate;
+mod dyn_value;
+#[cfg(feature = "const-generics")]
+mod inline_bytes;
+mod nan_box;
+mod null_is_none;
+mod or;
+mod small_int;
+mod small_string;
+mod stuff_enum;
+mod typed_immediate;
+
+#[cfg(feature = "const-generics")]
+pub use self::align_low_bits::AlignLowBits;
+#[cfg(target_pointer_width = "64")]
+pub use self::brads::BradsStrategy;
+#[cfg(feature = "const-generics")]
+pub use self::inline_bytes::InlineBytes;
+pub use self::{
+    align::{free_low_bits, ptr_mask},
+    char_immediate::CharImmediate,
+    dyn_value::{DynValue, DynValueStrategy},
+    nan_box::NanBoxStrategy,
+    null_is_none::NullIsNone,
+    or::{Or, OrOther},
+    small_int::SmallIntStrategy,
+    small_string::{InlineString, SmallString128Strategy},
+    typed_immediate::{StrategyFromTypedImmediate, TypedImmediate},
+};
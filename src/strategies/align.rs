@@ -0,0 +1,51 @@
+use core::mem;
+
+/// The number of low bits that are always zero in the address of a `T`, because of its
+/// alignment.
+///
+/// ```
+/// use stuff::strategies::free_low_bits;
+///
+/// assert_eq!(free_low_bits::<u8>(), 0);
+/// assert_eq!(free_low_bits::<u16>(), 1);
+/// ```
+pub fn free_low_bits<T>() -> u32 {
+    mem::align_of::<T>().trailing_zeros()
+}
+
+/// The mask of the bits that can be set in the address of a `T`, so all bits except the
+/// [`free_low_bits`].
+///
+/// ```
+/// use stuff::strategies::ptr_mask;
+///
+/// assert_eq!(ptr_mask::<u16>(), !1);
+/// ```
+pub fn ptr_mask<T>() -> usize {
+    !(mem::align_of::<T>() - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{free_low_bits, ptr_mask};
+
+    #[repr(align(8))]
+    struct Align8;
+
+    #[repr(align(16))]
+    struct Align16;
+
+    #[test]
+    fn free_bits() {
+        assert_eq!(free_low_bits::<u8>(), 0);
+        assert_eq!(free_low_bits::<Align8>(), 3);
+        assert_eq!(free_low_bits::<Align16>(), 4);
+    }
+
+    #[test]
+    fn mask() {
+        assert_eq!(ptr_mask::<u8>(), !0);
+        assert_eq!(ptr_mask::<Align8>(), !0b111);
+        assert_eq!(ptr_mask::<Align16>(), !0b1111);
+    }
+}
@@ -0,0 +1,107 @@
+use core::{convert::TryFrom, marker::PhantomData};
+
+use crate::{StuffingStrategy, Unstuffed};
+
+/// A strategy that stores small integers in the `BITS` low bits of the `usize` backend, which are
+/// always zero for pointers that are aligned to at least `2^BITS` bytes.
+///
+/// Only available with the `const-generics` feature, which requires Rust 1.57.
+///
+/// The integer is stored incremented by one, so if any of the low bits are set, the value is
+/// `other` data. Therefore, it must be smaller than `2^BITS - 1`, which is checked when stuffing.
+///
+/// Stuffing a pointer with any of the low bits set is a bug, since it would be confused with
+/// `other` data. This is checked with a debug assertion. Since the strategy doesn't know about
+/// the pointee type, the alignment of the pointee can't be checked at compile time. Use
+/// [`free_low_bits`](`super::free_low_bits`) to find out how many bits are available.
+///
+/// ```
+/// use stuff::{strategies::AlignLowBits, StuffedPtr};
+///
+/// let mut a = 1_u64;
+/// // `u64` is only 4 byte aligned on some 32 bit targets
+/// let ptr: StuffedPtr<u64, AlignLowBits<u8, 2>> = StuffedPtr::new_ptr(&mut a);
+/// let other: StuffedPtr<u64, AlignLowBits<u8, 2>> = StuffedPtr::new_other(2);
+///
+/// assert_eq!(ptr.ptr(), Some(&mut a as *mut u64));
+/// assert_eq!(other.other(), Some(2));
+/// ```
+pub struct AlignLowBits<O, const BITS: usize>(PhantomData<O>);
+
+impl<O, const BITS: usize> AlignLowBits<O, BITS> {
+    const MASK: usize = (1 << BITS) - 1;
+}
+
+impl<O, const BITS: usize> StuffingStrategy<usize> for AlignLowBits<O, BITS>
+where
+    O: Copy + Into<usize> + TryFrom<usize>,
+{
+    type Other = O;
+
+    fn stuff_other(inner: Self::Other) -> usize {
+        let value = inner.into();
+        assert!(value < Self::MASK, "Other value too big for the low bits");
+        value + 1
+    }
+
+    fn extract(data: usize) -> Unstuffed<usize, Self::Other> {
+        match data & Self::MASK {
+            0 => Unstuffed::Ptr(data),
+            tag => Unstuffed::Other(
+                O::try_from(tag - 1).unwrap_or_else(|_| panic!("Other value doesn't fit the type")),
+            ),
+        }
+    }
+
+    fn stuff_ptr(addr: usize) -> usize {
+        debug_assert_eq!(addr & Self::MASK, 0, "Pointer not aligned enough");
+        addr
+    }
+
+    fn is_other(data: usize) -> bool {
+        data & Self::MASK != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::undocumented_unsafe_blocks)]
+
+    use super::AlignLowBits;
+    use crate::StuffedPtr;
+
+    type Stuffed = StuffedPtr<u64, AlignLowBits<u8, 3>>;
+
+    #[test]
+    fn others() {
+        for other in 0..7 {
+            let stuffed: Stuffed = StuffedPtr::new_other(other);
+            assert_eq!(stuffed.other(), Some(other));
+            assert!(stuffed.ptr().is_none());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Other value too big for the low bits")]
+    fn other_too_big() {
+        let _: Stuffed = StuffedPtr::new_other(7);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Pointer not aligned enough")]
+    fn unaligned_pointer() {
+        let mut bytes = [0_u8; 2];
+        let unaligned = if (&bytes[0] as *const u8 as usize) % 2 == 1 {
+            &mut bytes[0]
+        } else {
+            &mut bytes[1]
+        };
+        let _: StuffedPtr<u8, AlignLowBits<u8, 1>> = StuffedPtr::new_ptr(unaligned);
+    }
+
+    #[test]
+    fn sound() {
+        crate::assert_strategy_sound!(AlignLowBits<u8, 3>, usize, [0, 1, 6]);
+    }
+}
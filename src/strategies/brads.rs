@@ -0,0 +1,110 @@
+use crate::{StuffingStrategy, Unstuffed};
+
+/// A strategy that stores either a pointer or an angle in binary radians (brads) on the `usize`
+/// backend. Only available on 64 bit platforms, since the angle needs the upper 32 bits.
+///
+/// The angle is a `u32` that's interpreted as a fraction of a full turn, so `0` is 0°,
+/// [`BradsStrategy::HALF_TURN`] is 180° and `u32::MAX` is just below 360°. Arithmetic on the
+/// angle wraps around naturally, so `wrapping_add` and `wrapping_sub` rotate by the given angle.
+///
+/// The lowest bit is set to tag the angle, so pointers must have an alignment of at least 2.
+///
+/// ```
+/// use stuff::{strategies::BradsStrategy, StuffedPtr};
+///
+/// let angle: StuffedPtr<(), BradsStrategy> =
+///     StuffedPtr::new_other(BradsStrategy::HALF_TURN.wrapping_add(BradsStrategy::HALF_TURN));
+/// assert_eq!(angle.other(), Some(0));
+/// ```
+pub struct BradsStrategy;
+
+impl BradsStrategy {
+    /// A quarter of a full turn (90°).
+    pub const QUARTER_TURN: u32 = 1 << 30;
+    /// Half of a full turn (180°).
+    pub const HALF_TURN: u32 = 1 << 31;
+
+    /// Convert an angle to radians.
+    pub fn to_radians(angle: u32) -> f64 {
+        f64::from(angle) * (core::f64::consts::PI / f64::from(Self::HALF_TURN))
+    }
+}
+
+impl StuffingStrategy<usize> for BradsStrategy {
+    type Other = u32;
+
+    fn stuff_other(inner: Self::Other) -> usize {
+        ((inner as usize) << 32) | 1
+    }
+
+    fn extract(data: usize) -> Unstuffed<usize, Self::Other> {
+        match data & 1 == 1 {
+            true => Unstuffed::Other((data >> 32) as u32),
+            false => Unstuffed::Ptr(data),
+        }
+    }
+
+    fn stuff_ptr(addr: usize) -> usize {
+        addr
+    }
+
+    fn is_other(data: usize) -> bool {
+        data & 1 == 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::undocumented_unsafe_blocks)]
+
+    use super::BradsStrategy;
+    use crate::StuffedPtr;
+
+    type Angle = StuffedPtr<u64, BradsStrategy>;
+
+    #[test]
+    fn boundaries() {
+        for &angle in &[
+            0,
+            1,
+            BradsStrategy::QUARTER_TURN,
+            BradsStrategy::HALF_TURN,
+            u32::MAX,
+        ] {
+            let stuffed: Angle = StuffedPtr::new_other(angle);
+            assert_eq!(stuffed.other(), Some(angle));
+            assert!(stuffed.ptr().is_none());
+        }
+    }
+
+    #[test]
+    fn wraps_around() {
+        let angle = u32::MAX.wrapping_add(BradsStrategy::QUARTER_TURN);
+        let stuffed: Angle = StuffedPtr::new_other(angle);
+        assert_eq!(stuffed.other(), Some(BradsStrategy::QUARTER_TURN - 1));
+    }
+
+    #[test]
+    fn radians() {
+        assert_eq!(BradsStrategy::to_radians(0), 0.0);
+        assert_eq!(
+            BradsStrategy::to_radians(BradsStrategy::HALF_TURN),
+            core::f64::consts::PI
+        );
+    }
+
+    #[test]
+    fn sound() {
+        crate::assert_strategy_sound!(
+            BradsStrategy,
+            usize,
+            [
+                0,
+                1,
+                BradsStrategy::QUARTER_TURN,
+                BradsStrategy::HALF_TURN,
+                u32::MAX
+            ]
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use crate::{RawHashable, StuffingStrategy, Unstuffed};
+
+/// A strategy that stores either a pointer or a `char` on the `u64` or `u128` backend.
+///
+/// The second highest bit of the backend is the tag, it's set for chars. The char is stored as its
+/// scalar value in the lowest 21 bits. Pointer addresses must not have the tag bit set, which is
+/// the case for all common 48 bit address spaces, and always for the `u128` backend.
+///
+/// The highest bit is left alone, so that this strategy can be combined with
+/// [`SmallIntStrategy`](`super::SmallIntStrategy`) using [`Or`](`super::Or`).
+///
+/// ```
+/// use stuff::{strategies::CharImmediate, StuffedPtr};
+///
+/// let char: StuffedPtr<(), CharImmediate, u64> = StuffedPtr::new_other('🦀');
+/// assert_eq!(char.other(), Some('🦀'));
+/// ```
+pub struct CharImmediate;
+
+macro_rules! impl_char_immediate {
+    ($($backend:ty),*) => {
+        $(
+            impl StuffingStrategy<$backend> for CharImmediate {
+                type Other = char;
+
+                fn stuff_other(inner: Self::Other) -> $backend {
+                    tag_bit::<$backend>() | <$backend>::from(inner as u32)
+                }
+
+                fn extract(data: $backend) -> Unstuffed<usize, Self::Other> {
+                    if data & tag_bit::<$backend>() == 0 {
+                        Unstuffed::Ptr(data as usize)
+                    } else {
+                        // only valid chars are ever stuffed, so this can only fail if a pointer
+                        // with the tag bit set was stuffed. `stuff_ptr` only checks that with a
+                        // debug assertion, so in release builds it ends up here
+                        let char = core::char::from_u32(data as u32)
+                            .unwrap_or(core::char::REPLACEMENT_CHARACTER);
+                        Unstuffed::Other(char)
+                    }
+                }
+
+                fn stuff_ptr(addr: usize) -> $backend {
+                    let addr = addr as $backend;
+                    debug_assert_eq!(addr & tag_bit::<$backend>(), 0, "Address too big");
+                    addr
+                }
+
+                fn is_other(data: $backend) -> bool {
+                    data & tag_bit::<$backend>() != 0
+                }
+            }
+
+            // every char and address is stuffed into exactly one integer
+            impl RawHashable<$backend> for CharImmediate {}
+        )*
+    };
+}
+
+impl_char_immediate!(u64, u128);
+
+/// The second highest bit of the backend `B`, which is set for chars.
+fn tag_bit<B>() -> B
+where
+    B: From<u8> + core::ops::Shl<usize, Output = B>,
+{
+    B::from(1) << (core::mem::size_of::<B>() * 8 - 2)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::undocumented_unsafe_blocks)]
+
+    use super::{tag_bit, CharImmediate};
+    use crate::{Backend, StuffedPtr};
+
+    const CHARS: [char; 5] = ['\0', 'a', 'ß', '🦀', '\u{10FFFF}'];
+
+    #[test]
+    fn chars() {
+        for &char in &CHARS {
+            let stuffed: StuffedPtr<u64, CharImmediate, u64> = StuffedPtr::new_other(char);
+            assert_eq!(stuffed.other(), Some(char));
+            assert!(stuffed.ptr().is_none());
+
+            let stuffed: StuffedPtr<u64, CharImmediate, u128> = StuffedPtr::new_other(char);
+            assert_eq!(stuffed.other(), Some(char));
+            assert!(stuffed.ptr().is_none());
+        }
+    }
+
+    #[test]
+    fn surrogates_are_replaced() {
+        // surrogates can't be constructed as `char`, so they never reach `stuff_other`, but a
+        // corrupted stored value could still contain one
+        for &surrogate in &[0xD800_u32, 0xDBFF, 0xDC00, 0xDFFF] {
+            let stored = <u64 as Backend>::set_ptr(
+                core::ptr::null_mut(),
+                tag_bit::<u64>() | u64::from(surrogate),
+            );
+            let stuffed: StuffedPtr<u64, CharImmediate, u64> =
+                unsafe { StuffedPtr::from_raw_stored(stored) };
+            assert_eq!(stuffed.other(), Some(core::char::REPLACEMENT_CHARACTER));
+
+            let stored = <u128 as Backend>::set_ptr(
+                core::ptr::null_mut(),
+                tag_bit::<u128>() | u128::from(surrogate),
+            );
+            let stuffed: StuffedPtr<u64, CharImmediate, u128> =
+                unsafe { StuffedPtr::from_raw_stored(stored) };
+            assert_eq!(stuffed.other(), Some(core::char::REPLACEMENT_CHARACTER));
+        }
+    }
+
+    #[test]
+    fn sound() {
+        crate::assert_strategy_sound!(CharImmediate, u64, ['\0', 'a', '🦀', '\u{10FFFF}']);
+        crate::assert_strategy_sound!(CharImmediate, u128, ['\0', 'a', '🦀', '\u{10FFFF}']);
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{StuffingStrategy, Unstuffed};
+
+/// The `other` data of [`DynValueStrategy`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DynValue {
+    /// The absence of a value
+    Nil,
+    /// A boolean
+    Bool(bool),
+    /// A 64 bit integer
+    Int(i64),
+    /// A 64 bit float
+    Float(f64),
+}
+
+/// A strategy for a value of a dynamically typed language on the `u128` backend. It stores either
+/// a pointer or a [`DynValue`].
+///
+/// The highest 3 bits contain the tag, where `0` is used for pointers, so the pointer address is
+/// stored unchanged. The payload of the other values is stored in the lower 64 bits.
+///
+/// ```
+/// use stuff::{
+///     strategies::{DynValue, DynValueStrategy},
+///     StuffedPtr,
+/// };
+///
+/// type Value = StuffedPtr<String, DynValueStrategy, u128>;
+///
+/// let value: Value = StuffedPtr::new_other(DynValue::Int(-5));
+/// assert_eq!(value.other(), Some(DynValue::Int(-5)));
+/// ```
+pub struct DynValueStrategy;
+
+impl DynValueStrategy {
+    const TAG_SHIFT: u32 = 125;
+
+    const TAG_PTR: u128 = 0;
+    const TAG_NIL: u128 = 1;
+    const TAG_BOOL: u128 = 2;
+    const TAG_INT: u128 = 3;
+    const TAG_FLOAT: u128 = 4;
+}
+
+impl StuffingStrategy<u128> for DynValueStrategy {
+    type Other = DynValue;
+
+    fn stuff_other(inner: Self::Other) -> u128 {
+        let (tag, payload) = match inner {
+            DynValue::Nil => (Self::TAG_NIL, 0),
+            DynValue::Bool(boolean) => (Self::TAG_BOOL, u64::from(boolean)),
+            DynValue::Int(int) => (Self::TAG_INT, int as u64),
+            DynValue::Float(float) => (Self::TAG_FLOAT, float.to_bits()),
+        };
+        (tag << Self::TAG_SHIFT) | u128::from(payload)
+    }
+
+    fn extract(data: u128) -> Unstuffed<usize, Self::Other> {
+        let payload = data as u64;
+        let other = match data >> Self::TAG_SHIFT {
+            Self::TAG_PTR => return Unstuffed::Ptr(data as usize),
+            Self::TAG_NIL => DynValue::Nil,
+            Self::TAG_BOOL => DynValue::Bool(payload != 0),
+            Self::TAG_INT => DynValue::Int(payload as i64),
+            Self::TAG_FLOAT => DynValue::Float(f64::from_bits(payload)),
+            _ => unreachable!("invalid tag"),
+        };
+        Unstuffed::Other(other)
+    }
+
+    fn stuff_ptr(addr: usize) -> u128 {
+        addr as u128
+    }
+
+    fn is_other(data: u128) -> bool {
+        match data >> Self::TAG_SHIFT {
+            Self::TAG_PTR => false,
+            Self::TAG_NIL | Self::TAG_BOOL | Self::TAG_INT | Self::TAG_FLOAT => true,
+            _ => unreachable!("invalid tag"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::undocumented_unsafe_blocks)]
+
+    use super::{DynValue, DynValueStrategy};
+    use crate::{StuffedPtr, StuffingStrategy};
+
+    type Value = StuffedPtr<u64, DynValueStrategy, u128>;
+
+    #[test]
+    fn others() {
+        for &value in &[
+            DynValue::Nil,
+            DynValue::Bool(false),
+            DynValue::Bool(true),
+            DynValue::Int(0),
+            DynValue::Int(i64::MIN),
+            DynValue::Int(i64::MAX),
+            DynValue::Float(0.0),
+            DynValue::Float(-0.0),
+            DynValue::Float(f64::INFINITY),
+            DynValue::Float(f64::MIN),
+        ] {
+            let stuffed: Value = StuffedPtr::new_other(value);
+            assert_eq!(stuffed.other(), Some(value));
+            assert!(stuffed.ptr().is_none());
+        }
+    }
+
+    #[test]
+    fn nan() {
+        let stuffed: Value = StuffedPtr::new_other(DynValue::Float(f64::NAN));
+        match stuffed.other() {
+            Some(DynValue::Float(float)) => assert_eq!(float.to_bits(), f64::NAN.to_bits()),
+            other => panic!("expected a float, got {:?}", other),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid tag")]
+    fn invalid_tag() {
+        <DynValueStrategy as StuffingStrategy<u128>>::is_other(5 << DynValueStrategy::TAG_SHIFT);
+    }
+
+    #[test]
+    fn sound() {
+        crate::assert_strategy_sound!(
+            DynValueStrategy,
+            u128,
+            [
+                DynValue::Nil,
+                DynValue::Bool(true),
+                DynValue::Int(i64::MIN),
+                DynValue::Float(-2.5)
+            ]
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{RawHashable, StuffingStrategy, Unstuffed};
+
+/// A strategy that stores either a pointer or exactly `N` bytes inline on the `u128` backend,
+/// for example for a small vector optimization. `N` must be at most 15, using it with a bigger
+/// `N` is a compile error.
+///
+/// Only available with the `const-generics` feature, which requires Rust 1.57.
+///
+/// Like [`SmallString128Strategy`](`super::SmallString128Strategy`), the most significant byte is
+/// used as the tag. It's zero for pointers and contains `N` with the highest bit set for inline
+/// bytes, which are stored in the remaining 15 bytes.
+///
+/// ```
+/// use stuff::{strategies::InlineBytes, StuffedPtr};
+///
+/// type SmallVec = StuffedPtr<Vec<u8>, InlineBytes<4>, u128>;
+///
+/// let inline: SmallVec = StuffedPtr::new_other([1, 2, 3, 4]);
+/// assert_eq!(inline.other(), Some([1, 2, 3, 4]));
+/// ```
+pub struct InlineBytes<const N: usize>;
+
+impl<const N: usize> InlineBytes<N> {
+    const TAG_BIT: u8 = 0x80;
+
+    const ASSERT_FITS: () = assert!(N <= 15, "Too many bytes to store inline");
+}
+
+impl<const N: usize> StuffingStrategy<u128> for InlineBytes<N> {
+    type Other = [u8; N];
+
+    fn stuff_other(inner: Self::Other) -> u128 {
+        let () = Self::ASSERT_FITS;
+        let mut buf = [0; 16];
+        buf[..N].copy_from_slice(&inner);
+        buf[15] = Self::TAG_BIT | N as u8;
+        u128::from_le_bytes(buf)
+    }
+
+    fn extract(data: u128) -> Unstuffed<usize, Self::Other> {
+        let () = Self::ASSERT_FITS;
+        if !Self::is_other(data) {
+            return Unstuffed::Ptr(data as usize);
+        }
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(&data.to_le_bytes()[..N]);
+        Unstuffed::Other(bytes)
+    }
+
+    fn stuff_ptr(addr: usize) -> u128 {
+        let () = Self::ASSERT_FITS;
+        addr as u128
+    }
+
+    fn is_other(data: u128) -> bool {
+        (data >> 120) as u8 & Self::TAG_BIT != 0
+    }
+}
+
+// the bytes after the first `N` are always zero
+impl<const N: usize> RawHashable<u128> for InlineBytes<N> {}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::undocumented_unsafe_blocks)]
+
+    use std::vec::Vec;
+
+    use super::InlineBytes;
+    use crate::StuffedPtr;
+
+    #[test]
+    fn empty() {
+        let stuffed: StuffedPtr<Vec<u8>, InlineBytes<0>, u128> = StuffedPtr::new_other([]);
+        assert_eq!(stuffed.other(), Some([]));
+        assert!(stuffed.ptr().is_none());
+
+        let null: StuffedPtr<Vec<u8>, InlineBytes<0>, u128> =
+            StuffedPtr::new_ptr(core::ptr::null_mut());
+        assert!(null.other().is_none());
+    }
+
+    #[test]
+    fn max_len() {
+        let bytes = [0xff; 15];
+        let stuffed: StuffedPtr<Vec<u8>, InlineBytes<15>, u128> = StuffedPtr::new_other(bytes);
+        assert_eq!(stuffed.other(), Some(bytes));
+        assert!(stuffed.ptr().is_none());
+    }
+
+    #[test]
+    fn sound() {
+        crate::assert_strategy_sound!(InlineBytes<0>, u128, [[]]);
+        crate::assert_strategy_sound!(InlineBytes<4>, u128, [[0; 4], [1, 2, 3, 4]]);
+        crate::assert_strategy_sound!(InlineBytes<15>, u128, [[0; 15], [0xff; 15]]);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{StuffingStrategy, Unstuffed};
+
+/// A NaN-boxing strategy on the `u64` backend. It stores either a pointer or an `f64`.
+///
+/// Pointers are hidden in the payload of quiet NaN values with the sign bit set. All NaN values
+/// stuffed as `other` data are replaced with a canonical NaN, so they can't be confused with
+/// pointers. Pointer addresses must fit into the lower 50 bits.
+///
+/// See the crate level documentation for an example of how this strategy is implemented.
+///
+/// ```
+/// use stuff::{strategies::NanBoxStrategy, StuffedPtr};
+///
+/// let float: StuffedPtr<(), NanBoxStrategy, u64> = StuffedPtr::new_other(123.5);
+/// assert_eq!(float.other(), Some(123.5));
+/// ```
+pub struct NanBoxStrategy;
+
+impl NanBoxStrategy {
+    /// The quiet NaN mask. All bits of it are set for pointers.
+    pub const QNAN: u64 = 0x7ffc000000000000;
+    /// The sign bit of an `f64`. It's set for pointers.
+    pub const SIGN_BIT: u64 = 0x8000000000000000;
+}
+
+impl StuffingStrategy<u64> for NanBoxStrategy {
+    type Other = f64;
+
+    fn stuff_other(inner: Self::Other) -> u64 {
+        if inner.is_nan() {
+            core::f64::NAN.to_bits()
+        } else {
+            inner.to_bits()
+        }
+    }
+
+    fn extract(data: u64) -> Unstuffed<usize, Self::Other> {
+        if (data & Self::QNAN) != Self::QNAN {
+            Unstuffed::Other(f64::from_bits(data))
+        } else {
+            Unstuffed::Ptr((data & !(Self::SIGN_BIT | Self::QNAN)) as usize)
+        }
+    }
+
+    fn stuff_ptr(addr: usize) -> u64 {
+        let addr = addr as u64;
+        debug_assert_eq!(
+            addr & (Self::SIGN_BIT | Self::QNAN),
+            0,
+            "Address too big for NaN-boxing"
+        );
+        Self::SIGN_BIT | Self::QNAN | addr
+    }
+
+    fn is_other(data: u64) -> bool {
+        (data & Self::QNAN) != Self::QNAN
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::undocumented_unsafe_blocks)]
+
+    use super::NanBoxStrategy;
+    use crate::StuffedPtr;
+
+    type Value = StuffedPtr<u64, NanBoxStrategy, u64>;
+
+    #[test]
+    fn floats() {
+        for &float in &[
+            0.0,
+            -0.0,
+            1.5,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::MIN,
+            f64::MAX,
+        ] {
+            let stuffed: Value = StuffedPtr::new_other(float);
+            assert_eq!(stuffed.other().map(f64::to_bits), Some(float.to_bits()));
+            assert!(stuffed.ptr().is_none());
+        }
+    }
+
+    #[test]
+    fn nan() {
+        for &nan in &[
+            f64::NAN,
+            -f64::NAN,
+            f64::from_bits(NanBoxStrategy::QNAN | 1),
+            f64::from_bits(NanBoxStrategy::SIGN_BIT | NanBoxStrategy::QNAN | 1),
+        ] {
+            let stuffed: Value = StuffedPtr::new_other(nan);
+            assert!(stuffed.other().unwrap().is_nan());
+            assert!(stuffed.ptr().is_none());
+        }
+    }
+
+    #[test]
+    fn sound() {
+        crate::assert_strategy_sound!(
+            NanBoxStrategy,
+            u64,
+            [0.0, -0.0, 1.5, -1e300, f64::INFINITY, f64::NEG_INFINITY]
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{StuffingStrategy, Unstuffed};
+
+/// A strategy where a null pointer is the `other` data, making
+/// `StuffedPtr<T, NullIsNone>` behave like an `Option<NonNull<T>>`.
+///
+/// Unlike the `()` strategy, where everything is a pointer, stuffing a null pointer results in
+/// `other` data.
+///
+/// ```
+/// use stuff::{strategies::NullIsNone, StuffedPtr};
+///
+/// let none: StuffedPtr<u8, NullIsNone> = StuffedPtr::new_ptr(core::ptr::null_mut());
+/// assert!(none.ptr().is_none());
+/// assert_eq!(none.other(), Some(()));
+/// ```
+pub struct NullIsNone;
+
+impl StuffingStrategy<usize> for NullIsNone {
+    type Other = ();
+
+    fn stuff_other(_inner: Self::Other) -> usize {
+        0
+    }
+
+    fn extract(data: usize) -> Unstuffed<usize, Self::Other> {
+        match data {
+            0 => Unstuffed::Other(()),
+            addr => Unstuffed::Ptr(addr),
+        }
+    }
+
+    fn stuff_ptr(addr: usize) -> usize {
+        addr
+    }
+
+    fn is_other(data: usize) -> bool {
+        data == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::undocumented_unsafe_blocks)]
+
+    use super::NullIsNone;
+    use crate::StuffedPtr;
+
+    #[test]
+    fn null_is_other() {
+        let stuffed: StuffedPtr<u64, NullIsNone> = StuffedPtr::new_ptr(core::ptr::null_mut());
+        assert_eq!(stuffed.other(), Some(()));
+        assert!(stuffed.ptr().is_none());
+
+        let stuffed: StuffedPtr<u64, NullIsNone> = StuffedPtr::new_other(());
+        assert_eq!(stuffed.other(), Some(()));
+
+        // the default strategy treats null as a pointer
+        let stuffed: StuffedPtr<u64, ()> = StuffedPtr::new_ptr(core::ptr::null_mut());
+        assert_eq!(stuffed.ptr(), Some(core::ptr::null_mut()));
+        assert!(stuffed.other().is_none());
+    }
+
+    #[test]
+    fn sound() {
+        crate::assert_strategy_sound!(NullIsNone, usize, [()]);
+    }
+}
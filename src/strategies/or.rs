@@ -0,0 +1,116 @@
+use core::marker::PhantomData;
+
+use crate::{StuffingStrategy, Unstuffed};
+
+/// A strategy that combines the `other` data of the strategies `A` and `B`, storing either a
+/// pointer, an `A::Other` or a `B::Other`.
+///
+/// `A` gets the first chance to claim the data in [`StuffingStrategy::extract`]. If `A` extracts
+/// a pointer, the data is passed on to `B`. Pointers are stuffed by `B`.
+///
+/// This only works if the two strategies use disjoint tags. Everything that `B` stuffs, `other`
+/// data and pointers alike, must be extracted as a pointer by `A`, and nothing that `A` stuffs
+/// may be stuffed by `B` as well. This is checked with a debug assertion for pointers, but not for
+/// `other` data, since it's stuffed by only one of them.
+///
+/// [`CharImmediate`](`super::CharImmediate`) tags chars with the second highest bit and
+/// [`SmallIntStrategy`](`super::SmallIntStrategy`) tags integers with the highest bit, so they can
+/// be combined:
+///
+/// ```
+/// use stuff::{
+///     strategies::{CharImmediate, Or, OrOther, SmallIntStrategy},
+///     StuffedPtr,
+/// };
+///
+/// type Value = StuffedPtr<u64, Or<CharImmediate, SmallIntStrategy>, u64>;
+///
+/// let char: Value = StuffedPtr::new_other(OrOther::A('a'));
+/// let int: Value = StuffedPtr::new_other(OrOther::B(-5));
+/// assert_eq!(char.other(), Some(OrOther::A('a')));
+/// assert_eq!(int.other(), Some(OrOther::B(-5)));
+///
+/// let mut a = 1;
+/// let ptr: Value = StuffedPtr::new_ptr(&mut a);
+/// assert_eq!(ptr.ptr(), Some(&mut a as *mut u64));
+/// ```
+pub struct Or<A, B>(PhantomData<(A, B)>);
+
+/// The `other` data of [`Or`], which is either the `other` data of `A` or of `B`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OrOther<A, B> {
+    /// The `other` data of the first strategy
+    A(A),
+    /// The `other` data of the second strategy
+    B(B),
+}
+
+impl<A, B, Backend> StuffingStrategy<Backend> for Or<A, B>
+where
+    A: StuffingStrategy<Backend>,
+    B: StuffingStrategy<Backend>,
+    Backend: Copy,
+{
+    type Other = OrOther<A::Other, B::Other>;
+
+    fn stuff_other(inner: Self::Other) -> Backend {
+        match inner {
+            OrOther::A(other) => A::stuff_other(other),
+            OrOther::B(other) => B::stuff_other(other),
+        }
+    }
+
+    fn extract(data: Backend) -> Unstuffed<usize, Self::Other> {
+        match A::extract(data) {
+            Unstuffed::Other(other) => Unstuffed::Other(OrOther::A(other)),
+            Unstuffed::Ptr(_) => B::extract(data).map_other(OrOther::B),
+        }
+    }
+
+    fn stuff_ptr(addr: usize) -> Backend {
+        let data = B::stuff_ptr(addr);
+        debug_assert!(!A::is_other(data), "Pointer claimed by the first strategy");
+        data
+    }
+
+    fn is_other(data: Backend) -> bool {
+        A::is_other(data) || B::is_other(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::undocumented_unsafe_blocks)]
+
+    use super::{Or, OrOther};
+    use crate::{
+        strategies::{CharImmediate, SmallIntStrategy},
+        StuffedPtr,
+    };
+
+    type Value = StuffedPtr<u64, Or<CharImmediate, SmallIntStrategy>, u64>;
+
+    #[test]
+    fn others() {
+        for &other in &[
+            OrOther::A('\0'),
+            OrOther::A('\u{10FFFF}'),
+            OrOther::B(0),
+            OrOther::B(i32::MIN),
+            OrOther::B(i32::MAX),
+        ] {
+            let stuffed: Value = StuffedPtr::new_other(other);
+            assert_eq!(stuffed.other(), Some(other));
+            assert!(stuffed.ptr().is_none());
+        }
+    }
+
+    #[test]
+    fn sound() {
+        crate::assert_strategy_sound!(
+            Or<CharImmediate, SmallIntStrategy>,
+            u64,
+            [OrOther::A('\0'), OrOther::A('🦀'), OrOther::B(0), OrOther::B(-1)]
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{RawHashable, StuffingStrategy, Unstuffed};
+
+/// A strategy that stores either a pointer or an `i32` on the `u64` backend.
+///
+/// The highest bit is the tag, it's set for integers. The integer is stored in bits 16 to 48.
+/// Pointer addresses must not have the highest bit set, which is the case for all common 48 bit
+/// address spaces.
+///
+/// ```
+/// use stuff::{strategies::SmallIntStrategy, StuffedPtr};
+///
+/// let int: StuffedPtr<(), SmallIntStrategy, u64> = StuffedPtr::new_other(-5);
+/// assert_eq!(int.other(), Some(-5));
+/// ```
+pub struct SmallIntStrategy;
+
+impl SmallIntStrategy {
+    /// The tag bit, which is set for integers.
+    pub const TAG_BIT: u64 = 1 << 63;
+
+    const SHIFT: u32 = 16;
+}
+
+impl StuffingStrategy<u64> for SmallIntStrategy {
+    type Other = i32;
+
+    fn stuff_other(inner: Self::Other) -> u64 {
+        Self::TAG_BIT | (u64::from(inner as u32) << Self::SHIFT)
+    }
+
+    fn extract(data: u64) -> Unstuffed<usize, Self::Other> {
+        if data & Self::TAG_BIT == 0 {
+            Unstuffed::Ptr(data as usize)
+        } else {
+            Unstuffed::Other((data >> Self::SHIFT) as u32 as i32)
+        }
+    }
+
+    fn stuff_ptr(addr: usize) -> u64 {
+        let addr = addr as u64;
+        debug_assert_eq!(addr & Self::TAG_BIT, 0, "Address too big");
+        addr
+    }
+
+    fn is_other(data: u64) -> bool {
+        data & Self::TAG_BIT != 0
+    }
+}
+
+// every integer and address is stuffed into exactly one `u64`
+impl RawHashable<u64> for SmallIntStrategy {}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::undocumented_unsafe_blocks)]
+
+    use super::SmallIntStrategy;
+    use crate::StuffedPtr;
+
+    type Value = StuffedPtr<u64, SmallIntStrategy, u64>;
+
+    #[test]
+    fn ints() {
+        for &int in &[0, 1, -1, 12345, -12345, i32::MIN, i32::MAX] {
+            let stuffed: Value = StuffedPtr::new_other(int);
+            assert_eq!(stuffed.other(), Some(int));
+            assert!(stuffed.ptr().is_none());
+        }
+    }
+
+    #[test]
+    fn sound() {
+        crate::assert_strategy_sound!(SmallIntStrategy, u64, [0, 1, -1, i32::MIN, i32::MAX]);
+    }
+}
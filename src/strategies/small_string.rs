@@ -0,0 +1,135 @@
+use crate::{StuffingStrategy, Unstuffed};
+
+/// The `other` data of [`SmallString128Strategy`], up to
+/// [`SmallString128Strategy::MAX_LEN`] bytes. Create it with [`SmallString128Strategy::inline`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InlineString {
+    len: u8,
+    bytes: [u8; 15],
+}
+
+impl InlineString {
+    /// The bytes of the string
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..usize::from(self.len)]
+    }
+}
+
+/// A strategy for a small string optimization on the `u128` backend. It stores either a pointer
+/// or up to [`SmallString128Strategy::MAX_LEN`] bytes inline.
+///
+/// The most significant byte is used as the tag. It's zero for pointers and contains the length
+/// with the highest bit set for inline strings. The remaining 15 bytes contain the string bytes.
+///
+/// The `other` data is an [`InlineString`], which can only be created with
+/// [`SmallString128Strategy::inline`], so it always fits.
+///
+/// ```
+/// use stuff::{strategies::SmallString128Strategy, StuffedPtr};
+///
+/// type SmallString = StuffedPtr<String, SmallString128Strategy, u128>;
+///
+/// let inline = SmallString128Strategy::inline(b"hello").unwrap();
+/// let string: SmallString = StuffedPtr::new_other(inline);
+///
+/// assert_eq!(string.other().unwrap().as_bytes(), b"hello");
+/// ```
+pub struct SmallString128Strategy;
+
+impl SmallString128Strategy {
+    /// The maximum amount of bytes that can be stored inline.
+    pub const MAX_LEN: usize = 15;
+
+    const TAG_BIT: u8 = 0x80;
+
+    /// Create the `other` data from the bytes, or `None` if they are longer than
+    /// [`SmallString128Strategy::MAX_LEN`].
+    pub fn inline(bytes: &[u8]) -> Option<InlineString> {
+        if bytes.len() > Self::MAX_LEN {
+            return None;
+        }
+        let mut buf = [0; 15];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        Some(InlineString {
+            len: bytes.len() as u8,
+            bytes: buf,
+        })
+    }
+}
+
+impl StuffingStrategy<u128> for SmallString128Strategy {
+    type Other = InlineString;
+
+    fn stuff_other(inner: Self::Other) -> u128 {
+        let mut buf = [0; 16];
+        buf[..15].copy_from_slice(&inner.bytes);
+        buf[15] = Self::TAG_BIT | inner.len;
+        u128::from_le_bytes(buf)
+    }
+
+    fn extract(data: u128) -> Unstuffed<usize, Self::Other> {
+        let buf = data.to_le_bytes();
+        let tag = buf[15];
+        if tag & Self::TAG_BIT == 0 {
+            return Unstuffed::Ptr(data as usize);
+        }
+        let mut bytes = [0; 15];
+        bytes.copy_from_slice(&buf[..15]);
+        Unstuffed::Other(InlineString {
+            len: tag & !Self::TAG_BIT,
+            bytes,
+        })
+    }
+
+    fn stuff_ptr(addr: usize) -> u128 {
+        addr as u128
+    }
+
+    fn is_other(data: u128) -> bool {
+        (data >> 120) as u8 & Self::TAG_BIT != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::undocumented_unsafe_blocks)]
+
+    use std::string::String;
+
+    use super::SmallString128Strategy;
+    use crate::StuffedPtr;
+
+    type SmallString = StuffedPtr<String, SmallString128Strategy, u128>;
+
+    fn round_trip(bytes: &[u8]) {
+        let inline = SmallString128Strategy::inline(bytes).unwrap();
+        let stuffed: SmallString = StuffedPtr::new_other(inline);
+        assert!(stuffed.ptr().is_none());
+
+        assert_eq!(stuffed.other().unwrap().as_bytes(), bytes);
+    }
+
+    #[test]
+    fn empty() {
+        round_trip(b"");
+    }
+
+    #[test]
+    fn max_len() {
+        round_trip(b"fifteen bytes!!");
+        assert!(SmallString128Strategy::inline(b"sixteen bytes!!!").is_none());
+    }
+
+    #[test]
+    fn sound() {
+        crate::assert_strategy_sound!(
+            SmallString128Strategy,
+            u128,
+            [
+                SmallString128Strategy::inline(b"").unwrap(),
+                SmallString128Strategy::inline(b"hello").unwrap(),
+                SmallString128Strategy::inline(&[0xff; 15]).unwrap(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,209 @@
+/// Define an enum that is a [`StuffingStrategy<usize>`](`crate::StuffingStrategy`) storing its
+/// variants in the low bits that are free because of pointer alignment.
+///
+/// The number of low bits is given after the enum name. Each variant is assigned a tag, which
+/// must be non-zero and fit into the low bits, as the tag `0` is used for pointers. So with 2 bits,
+/// there can be up to 3 variants next to the pointer, which needs an alignment of 4.
+///
+/// Variants may contain a single payload of a type that implements `Into<usize>` and
+/// `TryFrom<usize>`, like `u8` or `u16`. The payload is stored above the tag bits, so it must fit
+/// into the remaining bits. Invalid tags and payloads that don't fit are checked with debug
+/// assertions when stuffing.
+///
+/// The enum itself is the `other` data, so it must be `Copy`.
+///
+/// ```
+/// use stuff::{stuff_enum, StuffedPtr};
+///
+/// stuff_enum! {
+///     #[derive(Debug, Clone, Copy, PartialEq)]
+///     pub enum Immediate: 2 {
+///         Nil = 1,
+///         Int(u16) = 2,
+///     }
+/// }
+///
+/// let int: StuffedPtr<u32, Immediate> = StuffedPtr::new_other(Immediate::Int(5));
+/// assert_eq!(int.other(), Some(Immediate::Int(5)));
+/// ```
+#[macro_export]
+macro_rules! stuff_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident : $bits:literal {
+            $($variants:tt)*
+        }
+    ) => {
+        $crate::stuff_enum! {
+            @munch [$(#[$meta])* $vis $name $bits data] [] [] []
+            $($variants)*
+        }
+    };
+    (
+        @munch [$(#[$meta:meta])* $vis:vis $name:ident $bits:literal $data:ident]
+        [$($defs:tt)*] [$($stuff:tt)*] [$($extract:tt)*]
+        $variant:ident = $tag:literal $(, $($rest:tt)*)?
+    ) => {
+        $crate::stuff_enum! {
+            @munch [$(#[$meta])* $vis $name $bits $data]
+            [$($defs)* $variant,]
+            [$($stuff)* $name::$variant => tagged($tag, 0),]
+            [$($extract)* $tag => $name::$variant,]
+            $($($rest)*)?
+        }
+    };
+    (
+        @munch [$(#[$meta:meta])* $vis:vis $name:ident $bits:literal $data:ident]
+        [$($defs:tt)*] [$($stuff:tt)*] [$($extract:tt)*]
+        $variant:ident ($payload:ty) = $tag:literal $(, $($rest:tt)*)?
+    ) => {
+        $crate::stuff_enum! {
+            @munch [$(#[$meta])* $vis $name $bits $data]
+            [$($defs)* $variant($payload),]
+            [$($stuff)* $name::$variant(payload) => {
+                tagged($tag, ::core::convert::Into::<usize>::into(payload))
+            }]
+            [$($extract)* $tag => $name::$variant(
+                ::core::convert::TryFrom::try_from($data >> $bits)
+                    .unwrap_or_else(|_| panic!("Payload doesn't fit the type")),
+            ),]
+            $($($rest)*)?
+        }
+    };
+    (
+        @munch [$(#[$meta:meta])* $vis:vis $name:ident $bits:literal $data:ident]
+        [$($defs:tt)*] [$($stuff:tt)*] [$($extract:tt)*]
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $($defs)*
+        }
+
+        impl $crate::StuffingStrategy<usize> for $name {
+            type Other = Self;
+
+            fn stuff_other(inner: Self::Other) -> usize {
+                fn tagged(tag: usize, payload: usize) -> usize {
+                    debug_assert!(
+                        tag != 0 && tag < (1 << $bits),
+                        "Tag must be non-zero and fit into the low bits"
+                    );
+                    debug_assert_eq!(
+                        (payload << $bits) >> $bits,
+                        payload,
+                        "Payload too big for the bits above the tag"
+                    );
+                    (payload << $bits) | tag
+                }
+
+                match inner {
+                    $($stuff)*
+                }
+            }
+
+            fn extract($data: usize) -> $crate::Unstuffed<usize, Self::Other> {
+                let tag = $data & ((1 << $bits) - 1);
+                if tag == 0 {
+                    return $crate::Unstuffed::Ptr($data);
+                }
+                $crate::Unstuffed::Other(match tag {
+                    $($extract)*
+                    _ => unreachable!("invalid tag"),
+                })
+            }
+
+            fn stuff_ptr(addr: usize) -> usize {
+                debug_assert_eq!(addr & ((1 << $bits) - 1), 0, "Pointer not aligned enough");
+                addr
+            }
+
+            fn is_other($data: usize) -> bool {
+                $data & ((1 << $bits) - 1) != 0
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::undocumented_unsafe_blocks)]
+
+    use crate::StuffedPtr;
+
+    stuff_enum! {
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        enum Immediate: 2 {
+            Nil = 1,
+            Int(u16) = 2,
+            Byte(u8) = 3,
+        }
+    }
+
+    type Value = StuffedPtr<u64, Immediate>;
+
+    #[test]
+    fn others() {
+        for &immediate in &[
+            Immediate::Nil,
+            Immediate::Int(0),
+            Immediate::Int(u16::MAX),
+            Immediate::Byte(0),
+            Immediate::Byte(u8::MAX),
+        ] {
+            let stuffed: Value = StuffedPtr::new_other(immediate);
+            assert_eq!(stuffed.other(), Some(immediate));
+            assert!(stuffed.ptr().is_none());
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Tag must be non-zero and fit into the low bits")]
+    fn zero_tag() {
+        stuff_enum! {
+            #[derive(Clone, Copy)]
+            enum ZeroTag: 2 {
+                Nil = 0,
+            }
+        }
+
+        let _: StuffedPtr<u64, ZeroTag> = StuffedPtr::new_other(ZeroTag::Nil);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Tag must be non-zero and fit into the low bits")]
+    fn tag_too_big() {
+        stuff_enum! {
+            #[derive(Clone, Copy)]
+            enum BigTag: 2 {
+                Nil = 4,
+            }
+        }
+
+        let _: StuffedPtr<u64, BigTag> = StuffedPtr::new_other(BigTag::Nil);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Payload too big for the bits above the tag")]
+    fn payload_too_big() {
+        stuff_enum! {
+            #[derive(Clone, Copy)]
+            enum Wide: 2 {
+                Int(usize) = 1,
+            }
+        }
+
+        let _: StuffedPtr<u64, Wide> = StuffedPtr::new_other(Wide::Int(usize::MAX));
+    }
+
+    #[test]
+    fn sound() {
+        crate::assert_strategy_sound!(
+            Immediate,
+            usize,
+            [Immediate::Nil, Immediate::Int(u16::MAX), Immediate::Byte(0)]
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use core::{convert::TryInto, marker::PhantomData};
+
+use crate::{Backend, StuffingStrategy, Unstuffed};
+
+/// A value type that knows how to encode its immediates into the backend `B`.
+///
+/// This is a simpler alternative to implementing [`StuffingStrategy`] directly. The type is
+/// usually an enum with one variant standing in for pointers, while all other variants are
+/// immediates. It's turned into a strategy with [`StrategyFromTypedImmediate`], which stores
+/// pointer addresses as they are.
+///
+/// The encoding of the immediates must never overlap with a valid pointer address.
+pub trait TypedImmediate<B>: Copy {
+    /// Encode the immediate, or return `None` if `self` is the variant standing in for pointers.
+    fn to_stuffed(&self) -> Option<B>;
+
+    /// Decode the data. If `data` doesn't contain an immediate encoded by
+    /// [`TypedImmediate::to_stuffed`], this must return the variant standing in for pointers.
+    fn from_stuffed(data: B) -> Self;
+}
+
+/// A [`StuffingStrategy`] for a [`TypedImmediate`] type `E`.
+///
+/// ```
+/// use stuff::{
+///     strategies::{StrategyFromTypedImmediate, TypedImmediate},
+///     StuffedPtr,
+/// };
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Value {
+///     Int(u16),
+///     Ptr,
+/// }
+///
+/// impl TypedImmediate<usize> for Value {
+///     fn to_stuffed(&self) -> Option<usize> {
+///         match *self {
+///             Value::Int(int) => Some((usize::from(int) << 1) | 1),
+///             Value::Ptr => None,
+///         }
+///     }
+///
+///     fn from_stuffed(data: usize) -> Self {
+///         match data & 1 {
+///             1 => Value::Int((data >> 1) as u16),
+///             _ => Value::Ptr,
+///         }
+///     }
+/// }
+///
+/// let int: StuffedPtr<u64, StrategyFromTypedImmediate<Value>> = StuffedPtr::new_other(Value::Int(5));
+/// assert_eq!(int.other(), Some(Value::Int(5)));
+/// ```
+pub struct StrategyFromTypedImmediate<E>(PhantomData<E>);
+
+impl<E, B> StuffingStrategy<B> for StrategyFromTypedImmediate<E>
+where
+    E: TypedImmediate<B>,
+    B: Backend + Copy + TryInto<usize>,
+    usize: TryInto<B>,
+{
+    type Other = E;
+
+    fn stuff_other(inner: Self::Other) -> B {
+        inner
+            .to_stuffed()
+            .unwrap_or_else(|| panic!("Tried to stuff the pointer variant as other data"))
+    }
+
+    fn extract(data: B) -> Unstuffed<usize, Self::Other> {
+        let immediate = E::from_stuffed(data);
+        match immediate.to_stuffed() {
+            Some(_) => Unstuffed::Other(immediate),
+            None => Unstuffed::Ptr(
+                data.try_into()
+                    .unwrap_or_else(|_| panic!("Pointer value too big for usize")),
+            ),
+        }
+    }
+
+    fn stuff_ptr(addr: usize) -> B {
+        addr.try_into()
+            .unwrap_or_else(|_| panic!("Address in `stuff_ptr` too big"))
+    }
+
+    fn is_other(data: B) -> bool {
+        E::from_stuffed(data).to_stuffed().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::undocumented_unsafe_blocks)]
+
+    use super::{StrategyFromTypedImmediate, TypedImmediate};
+    use crate::StuffedPtr;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Value {
+        Int(u16),
+        Bool(bool),
+        Ptr,
+    }
+
+    impl TypedImmediate<usize> for Value {
+        fn to_stuffed(&self) -> Option<usize> {
+            match *self {
+                Value::Int(int) => Some((usize::from(int) << 2) | 0b01),
+                Value::Bool(boolean) => Some((usize::from(boolean) << 2) | 0b10),
+                Value::Ptr => None,
+            }
+        }
+
+        fn from_stuffed(data: usize) -> Self {
+            match data & 0b11 {
+                0b01 => Value::Int((data >> 2) as u16),
+                0b10 => Value::Bool(data >> 2 != 0),
+                _ => Value::Ptr,
+            }
+        }
+    }
+
+    type Stuffed = StuffedPtr<u32, StrategyFromTypedImmediate<Value>>;
+
+    #[test]
+    fn immediates() {
+        for &value in &[
+            Value::Int(0),
+            Value::Int(u16::MAX),
+            Value::Bool(false),
+            Value::Bool(true),
+        ] {
+            let stuffed: Stuffed = StuffedPtr::new_other(value);
+            assert_eq!(stuffed.other(), Some(value));
+            assert!(stuffed.ptr().is_none());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "pointer variant")]
+    fn stuff_pointer_variant() {
+        let _: Stuffed = StuffedPtr::new_other(Value::Ptr);
+    }
+
+    #[test]
+    fn sound() {
+        crate::assert_strategy_sound!(
+            StrategyFromTypedImmediate<Value>,
+            usize,
+            [Value::Int(u16::MAX), Value::Bool(true)]
+        );
+    }
+}
@@ -30,8 +30,38 @@ pub trait StuffingStrategy<B> {
     ///
     /// The default implementation just returns the address directly.
     fn stuff_ptr(addr: usize) -> B;
+
+    /// Like [`StuffingStrategy::stuff_ptr`], but returns `None` instead of panicking if the
+    /// address can't be stuffed.
+    ///
+    /// The default implementation calls `stuff_ptr`, so it panics whenever `stuff_ptr` does.
+    fn try_stuff_ptr(addr: usize) -> Option<B> {
+        Some(Self::stuff_ptr(addr))
+    }
+
+    /// Returns `true` if the data contains `other` data and `false` if it contains a pointer.
+    ///
+    /// The default implementation uses [`StuffingStrategy::extract`] and discards the result.
+    /// Strategies can override it with a cheaper check of their tag, but it must agree with
+    /// `extract`.
+    fn is_other(data: B) -> bool {
+        match Self::extract(data) {
+            Unstuffed::Ptr(_) => false,
+            Unstuffed::Other(_) => true,
+        }
+    }
 }
 
+/// The simplest strategy, which can only store pointers. `other` data is stuffed as `0`, so it's
+/// indistinguishable from a null pointer.
+///
+/// The address is converted to and from `B` with `TryInto`. [`StuffingStrategy::stuff_ptr`]
+/// panics if the address doesn't fit into `B`, and [`StuffingStrategy::extract`] panics if the
+/// data doesn't fit into a `usize`. This can't happen for the backends provided by this crate,
+/// which are all at least pointer sized, but it can for custom backends that are smaller. Use
+/// [`StuffedPtr::try_new_ptr`](`crate::StuffedPtr::try_new_ptr`) to avoid the panic in that case,
+/// which uses [`StuffingStrategy::try_stuff_ptr`]. Data that was stuffed by this strategy always
+/// fits into a `usize` again, so `extract` doesn't panic for values of a `StuffedPtr`.
 impl<B> StuffingStrategy<B> for ()
 where
     B: Backend + Default + TryInto<usize>,
@@ -55,6 +85,10 @@ where
         addr.try_into()
             .unwrap_or_else(|_| panic!("Address in `stuff_ptr` too big"))
     }
+
+    fn try_stuff_ptr(addr: usize) -> Option<B> {
+        addr.try_into().ok()
+    }
 }
 
 #[cfg(test)]
@@ -70,7 +104,7 @@ pub(crate) mod test_strategies {
             impl StuffingStrategy<usize> for $ty {
                 type Other = Self;
 
-                #[allow(clippy::forget_copy)]
+                #[allow(forgetting_copy_types)]
                 fn stuff_other(inner: Self::Other) -> usize {
                     core::mem::forget(inner);
                     usize::MAX
@@ -91,7 +125,7 @@ pub(crate) mod test_strategies {
             impl StuffingStrategy<u64> for $ty {
                 type Other = Self;
 
-                #[allow(clippy::forget_copy)]
+                #[allow(forgetting_copy_types)]
                 fn stuff_other(inner: Self::Other) -> u64 {
                     core::mem::forget(inner);
                     u64::MAX
@@ -112,7 +146,7 @@ pub(crate) mod test_strategies {
             impl StuffingStrategy<u128> for $ty {
                 type Other = Self;
 
-                #[allow(clippy::forget_copy)]
+                #[allow(forgetting_copy_types)]
                 fn stuff_other(inner: Self::Other) -> u128 {
                     core::mem::forget(inner);
                     u128::MAX
@@ -129,6 +163,27 @@ pub(crate) mod test_strategies {
                     addr as u128
                 }
             }
+
+            impl StuffingStrategy<u32> for $ty {
+                type Other = Self;
+
+                #[allow(forgetting_copy_types)]
+                fn stuff_other(inner: Self::Other) -> u32 {
+                    core::mem::forget(inner);
+                    u32::MAX
+                }
+
+                fn extract(data: u32) -> Unstuffed<usize, Self::Other> {
+                    match data == u32::MAX {
+                        true => Unstuffed::Other($ty),
+                        false => Unstuffed::Ptr(data as usize),
+                    }
+                }
+
+                fn stuff_ptr(addr: usize) -> u32 {
+                    addr as u32
+                }
+            }
         };
     }
 
@@ -147,4 +202,27 @@ pub(crate) mod test_strategies {
     }
 
     impl_usize_max_zst!(HasDebug);
+
+    // this one keeps the other in the upper bits and sets the lowest bit, so it can only store
+    // pointers with an alignment of at least 2
+    pub struct LowBitU16;
+
+    impl StuffingStrategy<usize> for LowBitU16 {
+        type Other = u16;
+
+        fn stuff_other(inner: Self::Other) -> usize {
+            (usize::from(inner) << 1) | 1
+        }
+
+        fn extract(data: usize) -> Unstuffed<usize, Self::Other> {
+            match data & 1 == 1 {
+                true => Unstuffed::Other((data >> 1) as u16),
+                false => Unstuffed::Ptr(data),
+            }
+        }
+
+        fn stuff_ptr(addr: usize) -> usize {
+            addr
+        }
+    }
 }
@@ -1,11 +1,38 @@
-#![allow(dead_code)]
-
-use core::marker::PhantomData;
+use core::{
+    fmt::{Debug, Formatter},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
 
 use sptr::Strict;
 
-use crate::Backend;
+use crate::{Backend, StuffedPtr, StuffingStrategy, Unstuffed};
+
+// const generics are only parsed in a separate file, to keep the MSRV
+#[cfg(feature = "const-generics")]
+mod align_tag;
 
+#[cfg(feature = "const-generics")]
+pub use self::align_tag::AlignTag;
+
+/// A pointer with a tag stored in the bits of the address that aren't needed, governed by the
+/// [`TaggingStrategy`] `S`. Like [`StuffedPtr`](`crate::StuffedPtr`), it's stored in the
+/// [`Backend`] `B`.
+///
+/// Unlike `StuffedPtr`, which contains *either* a pointer or `other` data, a `TaggedPtr` always
+/// contains a pointer *and* a tag.
+///
+/// Only available with the `tagged` feature.
+///
+/// ```
+/// use stuff::{BoolTag, TaggedPtr};
+///
+/// let mut a = 1_u64;
+/// let tagged: TaggedPtr<u64, BoolTag> = TaggedPtr::new(&mut a, true);
+///
+/// assert_eq!(tagged.get_ptr(), &mut a as *mut u64);
+/// assert!(tagged.get_tag());
+/// ```
 pub struct TaggedPtr<T, S, B = usize>(B::Stored, PhantomData<(S, *mut T)>)
 where
     B: Backend;
@@ -15,30 +42,139 @@ where
     S: TaggingStrategy<B>,
     B: Backend,
 {
+    /// Create a new `TaggedPtr` from a pointer and a tag
     pub fn new(ptr: *mut T, tag: S::Tag) -> Self {
+        S::assert_fits::<T>();
         let addr = Strict::addr(ptr);
         let tagged = S::set(addr, tag);
-        let stored = B::set_ptr(ptr, tagged);
+        let stored = B::set_ptr(ptr as *mut (), tagged);
         TaggedPtr(stored, PhantomData)
     }
 
+    /// Get the pointer, without the tag
     pub fn get_ptr(&self) -> *mut T {
         let (provenance, stored) = B::get_ptr(self.0);
         let addr = S::get_ptr_addr(stored);
-        Strict::with_addr(provenance, addr)
+        Strict::with_addr(provenance as *mut T, addr)
+    }
+
+    /// Get a shared reference to the pointee, ignoring the tag
+    ///
+    /// ```
+    /// use stuff::{BoolTag, TaggedPtr};
+    ///
+    /// // a linked list where the tag marks removed nodes
+    /// struct Node {
+    ///     value: u32,
+    ///     next: Option<TaggedPtr<Node, BoolTag>>,
+    /// }
+    ///
+    /// let mut third = Node { value: 3, next: None };
+    /// let mut second = Node { value: 2, next: Some(TaggedPtr::new(&mut third, false)) };
+    /// let first = Node { value: 1, next: Some(TaggedPtr::new(&mut second, true)) };
+    ///
+    /// let mut live = vec![first.value];
+    /// let mut next = first.next;
+    /// while let Some(node) = next {
+    ///     // SAFETY: all nodes are alive, and nothing mutates them
+    ///     let node_ref = unsafe { node.get_ref() };
+    ///     if !node.get_tag() {
+    ///         live.push(node_ref.value);
+    ///     }
+    ///     next = node_ref.next;
+    /// }
+    /// assert_eq!(live, [1, 3]);
+    /// ```
+    ///
+    /// # Safety
+    /// The pointer must be valid for reads, aligned and point to an initialized `T`, as described
+    /// in [`pointer::as_ref`](https://doc.rust-lang.org/std/primitive.pointer.html#method.as_ref).
+    /// The pointee must not be mutated for as long as the reference is alive.
+    pub unsafe fn get_ref(&self) -> &T {
+        &*self.get_ptr()
+    }
+
+    /// Get a mutable reference to the pointee, ignoring the tag
+    ///
+    /// # Safety
+    /// The pointer must be valid for reads and writes, aligned and point to an initialized `T`,
+    /// as described in
+    /// [`pointer::as_mut`](https://doc.rust-lang.org/std/primitive.pointer.html#method.as_mut).
+    /// The pointee must not be accessed through any other pointer for as long as the reference is
+    /// alive.
+    pub unsafe fn get_mut(&mut self) -> &mut T {
+        &mut *self.get_ptr()
     }
 
+    /// Get the tag
     pub fn get_tag(&self) -> S::Tag {
         let stored = B::get_int(self.0);
         S::get_tag(stored)
     }
 
-    pub fn set_tag(&self, tag: S::Tag) -> Self {
+    /// Set the tag, keeping the pointer
+    pub fn set_tag_in_place(&mut self, tag: S::Tag) {
         let (provenance, stored) = B::get_ptr(self.0);
         let ptr_addr = S::get_ptr_addr(stored);
         let addr = S::set(ptr_addr, tag);
-        let stored = B::set_ptr(provenance, addr);
-        TaggedPtr(stored, PhantomData)
+        self.0 = B::set_ptr(provenance, addr);
+    }
+
+    /// Create a new `TaggedPtr` with the same pointer and a new tag
+    pub fn with_tag(mut self, tag: S::Tag) -> Self {
+        self.set_tag_in_place(tag);
+        self
+    }
+}
+
+impl<T, S, B> TaggedPtr<T, S, B>
+where
+    S: TaggingStrategy<B>,
+    S::Tag: Default,
+    B: Backend,
+{
+    /// Create a `TaggedPtr` from a `StuffedPtr` whose `other` type is the tag.
+    ///
+    /// A pointer is tagged with `S::Tag::default()`, `other` data becomes the tag of a null
+    /// pointer. This is the inverse of [`StuffedPtr::from_tagged`].
+    pub fn from_stuffed<S2>(stuffed: StuffedPtr<T, S2, B>) -> Self
+    where
+        S2: StuffingStrategy<B, Other = S::Tag>,
+    {
+        match stuffed.unstuff() {
+            Unstuffed::Ptr(ptr) => TaggedPtr::new(ptr, S::Tag::default()),
+            Unstuffed::Other(tag) => TaggedPtr::new(core::ptr::null_mut(), tag),
+        }
+    }
+}
+
+impl<T, S, B> StuffedPtr<T, S, B>
+where
+    S: StuffingStrategy<B>,
+    B: Backend,
+{
+    /// Create a `StuffedPtr` from a `TaggedPtr` whose tag type is the `other` type.
+    ///
+    /// If the tag is `S::Other::default()`, the pointer is kept. Every other tag becomes the
+    /// `other` data, **and the pointer is discarded**. The default value of the tag is therefore
+    /// reserved for pointers and can't be used as `other` data.
+    ///
+    /// This is the inverse of [`TaggedPtr::from_stuffed`]. Converting a `StuffedPtr` to a
+    /// `TaggedPtr` and back always yields the original value (except for `other` data that is
+    /// equal to the default tag).
+    ///
+    /// Only available with the `tagged` feature.
+    pub fn from_tagged<S2>(tagged: TaggedPtr<T, S2, B>) -> Self
+    where
+        S2: TaggingStrategy<B, Tag = S::Other>,
+        S::Other: Default + PartialEq,
+    {
+        let tag = tagged.get_tag();
+        if tag == S::Other::default() {
+            StuffedPtr::new_ptr(tagged.get_ptr())
+        } else {
+            StuffedPtr::new_other(tag)
+        }
     }
 }
 
@@ -47,21 +183,317 @@ where
     B: Backend,
 {
     fn clone(&self) -> Self {
-        TaggedPtr(self.0, self.1)
+        *self
     }
 }
 
 impl<T, S, B> Copy for TaggedPtr<T, S, B> where B: Backend {}
 
+// SAFETY: The tag is just bits next to the address, so only the pointee matters. Like `Box<T>`,
+//         it can be accessed from the thread the `TaggedPtr` was sent to, so it must be `Send`.
+//         Dereferencing the pointer is unsafe, and it's the users responsibility to not send
+//         dangling pointers. The tag can be copied out on the other thread, so it must be `Send`.
+unsafe impl<T, S, B> Send for TaggedPtr<T, S, B>
+where
+    T: Send,
+    S: TaggingStrategy<B>,
+    S::Tag: Send,
+    B: Backend,
+{
+}
+
+// SAFETY: Like `&T`, a shared `TaggedPtr` gives access to the pointee (see `get_ref`) and the tag
+//         from multiple threads, so both must be `Sync`. See above for the pointer.
+unsafe impl<T, S, B> Sync for TaggedPtr<T, S, B>
+where
+    T: Sync,
+    S: TaggingStrategy<B>,
+    S::Tag: Sync,
+    B: Backend,
+{
+}
+
+impl<T, S, B> Debug for TaggedPtr<T, S, B>
+where
+    S: TaggingStrategy<B>,
+    S::Tag: Debug,
+    B: Backend,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TaggedPtr")
+            .field("ptr", &self.get_ptr())
+            .field("tag", &self.get_tag())
+            .finish()
+    }
+}
+
+/// Two `TaggedPtr`s are equal if both their addresses and their tags are equal.
+impl<T, S, B> PartialEq for TaggedPtr<T, S, B>
+where
+    S: TaggingStrategy<B>,
+    S::Tag: PartialEq,
+    B: Backend,
+{
+    fn eq(&self, other: &Self) -> bool {
+        Strict::addr(self.get_ptr()) == Strict::addr(other.get_ptr())
+            && self.get_tag() == other.get_tag()
+    }
+}
+
+impl<T, S, B> Eq for TaggedPtr<T, S, B>
+where
+    S: TaggingStrategy<B>,
+    S::Tag: PartialEq + Eq,
+    B: Backend,
+{
+}
+
+impl<T, S, B> Hash for TaggedPtr<T, S, B>
+where
+    S: TaggingStrategy<B>,
+    S::Tag: Hash,
+    B: Backend,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Strict::addr(self.get_ptr()).hash(state);
+        self.get_tag().hash(state);
+    }
+}
+
+/// A trait that describes how to store a tag next to a pointer address in the backend `B`.
+///
+/// [`TaggingStrategy::set`] merges the tag into the address, and [`TaggingStrategy::get_tag`] and
+/// [`TaggingStrategy::get_ptr_addr`] must split them again, so that the address returned from
+/// `get_ptr_addr` is the exact address passed to `set`, with all tag bits masked out.
+///
+/// Only available with the `tagged` feature.
 pub trait TaggingStrategy<B> {
+    /// The type of the tag.
     type Tag: Copy;
 
+    /// Get the tag from the data.
     fn get_tag(data: B) -> Self::Tag;
 
+    /// Get the pointer address from the data, without the tag.
     fn get_ptr_addr(data: B) -> usize;
 
+    /// Store the tag next to the pointer address.
     fn set(addr: usize, tag: Self::Tag) -> B;
+
+    /// Called whenever a `TaggedPtr` to a `T` is created. Strategies can use it to reject pointee
+    /// types whose pointers don't have room for the tag, ideally at compile time by evaluating an
+    /// associated `const`. The default implementation accepts all types.
+    fn assert_fits<T>() {}
+}
+
+/// A tagging strategy that stores a `bool` in the lowest bit of the address, for example for the
+/// color of a node in a red-black tree. The pointee must have an alignment of at least 2.
+///
+/// Only available with the `tagged` feature.
+pub struct BoolTag;
+
+impl TaggingStrategy<usize> for BoolTag {
+    type Tag = bool;
+
+    fn get_tag(data: usize) -> Self::Tag {
+        data & 1 == 1
+    }
+
+    fn get_ptr_addr(data: usize) -> usize {
+        data & !1
+    }
+
+    fn set(addr: usize, tag: Self::Tag) -> usize {
+        debug_assert_eq!(addr & 1, 0, "Pointer not aligned enough");
+        addr | usize::from(tag)
+    }
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    #![allow(clippy::undocumented_unsafe_blocks)]
+
+    use std::{boxed::Box, collections::HashSet, format};
+
+    use paste::paste;
+
+    use super::{BoolTag, TaggedPtr, TaggingStrategy};
+    use crate::{StuffedPtr, StuffingStrategy, Unstuffed};
+
+    // stores a `u8` tag in the highest byte of the backend
+    struct HighByte;
+
+    macro_rules! impl_high_byte {
+        ($backend:ident) => {
+            impl TaggingStrategy<$backend> for HighByte {
+                type Tag = u8;
+
+                fn get_tag(data: $backend) -> Self::Tag {
+                    (data >> ($backend::BITS - 8)) as u8
+                }
+
+                fn get_ptr_addr(data: $backend) -> usize {
+                    (data & ($backend::MAX >> 8)) as usize
+                }
+
+                fn set(addr: usize, tag: Self::Tag) -> $backend {
+                    (addr as $backend) | ($backend::from(tag) << ($backend::BITS - 8))
+                }
+            }
+
+            paste! {
+                #[test]
+                fn [<high_byte_ $backend>]() {
+                    let mut a = 1_u64;
+                    let a_ptr: *mut u64 = &mut a;
+                    let tagged: TaggedPtr<u64, HighByte, $backend> = TaggedPtr::new(a_ptr, 0xff);
+                    assert_eq!(tagged.get_tag(), 0xff);
+                    assert_eq!(tagged.get_ptr(), a_ptr);
+
+                    let tagged = tagged.with_tag(0x12);
+                    assert_eq!(tagged.get_tag(), 0x12);
+                    unsafe { *tagged.get_ptr() += 1 };
+                    assert_eq!(a, 2);
+                }
+            }
+        };
+    }
+
+    impl_high_byte!(u128);
+    impl_high_byte!(u64);
+    // on 32 bit, the highest byte of the address might be used
+    #[cfg(target_pointer_width = "64")]
+    impl_high_byte!(usize);
+
+    #[test]
+    fn keeps_ptr() {
+        let mut a = 1_u64;
+        let tagged: TaggedPtr<u64, BoolTag> = TaggedPtr::new(&mut a, false);
+        let tagged = tagged.with_tag(true);
+
+        unsafe { *tagged.get_ptr() += 1 };
+        assert_eq!(a, 2);
+    }
+
+    #[test]
+    fn bool_tag() {
+        let tagged: TaggedPtr<u64, BoolTag> = TaggedPtr::new(Box::into_raw(Box::new(5)), true);
+        assert!(tagged.get_tag());
+
+        let tagged = tagged.with_tag(false);
+        assert!(!tagged.get_tag());
+
+        let boxed = unsafe { Box::from_raw(tagged.get_ptr()) };
+        assert_eq!(*boxed, 5);
+    }
+
+    #[test]
+    fn get_ref_get_mut() {
+        let mut a = 1_u64;
+        let mut tagged: TaggedPtr<u64, BoolTag> = TaggedPtr::new(&mut a, true);
+        unsafe { *tagged.get_mut() += 1 };
+        assert_eq!(unsafe { *tagged.get_ref() }, 2);
+        assert!(tagged.get_tag());
+        assert_eq!(a, 2);
+    }
+
+    #[test]
+    fn send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<TaggedPtr<u64, BoolTag>>();
+        assert_send_sync::<TaggedPtr<u64, HighByte, u128>>();
+
+        let mut a = 1_u64;
+        let tagged: TaggedPtr<u64, BoolTag> = TaggedPtr::new(&mut a, true);
+        std::thread::scope(|scope| {
+            scope.spawn(|| unsafe { *tagged.get_ptr() += 1 });
+        });
+        assert!(tagged.get_tag());
+        assert_eq!(a, 2);
+    }
+
+    #[test]
+    fn set_tag_in_place() {
+        let mut a = 1_u64;
+        let mut tagged: TaggedPtr<u64, BoolTag> = TaggedPtr::new(&mut a, false);
+        tagged.set_tag_in_place(true);
+        assert!(tagged.get_tag());
+
+        unsafe { *tagged.get_ptr() += 1 };
+        assert_eq!(a, 2);
+    }
+
+    // stores a `u8` in the highest byte and marks it with the lowest bit, compatible with
+    // `HighByte` tags
+    struct HighByteOther;
+
+    impl StuffingStrategy<u64> for HighByteOther {
+        type Other = u8;
+
+        fn stuff_other(inner: Self::Other) -> u64 {
+            (u64::from(inner) << 56) | 1
+        }
+
+        fn extract(data: u64) -> Unstuffed<usize, Self::Other> {
+            match data & 1 == 1 {
+                true => Unstuffed::Other((data >> 56) as u8),
+                false => Unstuffed::Ptr(data as usize),
+            }
+        }
+
+        fn stuff_ptr(addr: usize) -> u64 {
+            addr as u64
+        }
+    }
+
+    #[test]
+    fn stuffed_round_trip() {
+        let mut a = 1_u64;
+        let a_ptr: *mut u64 = &mut a;
+
+        let stuffed: StuffedPtr<u64, HighByteOther, u64> = StuffedPtr::new_ptr(a_ptr);
+        let tagged: TaggedPtr<u64, HighByte, u64> = TaggedPtr::from_stuffed(stuffed);
+        assert_eq!(tagged.get_tag(), 0);
+        assert_eq!(tagged.get_ptr(), a_ptr);
+        let back: StuffedPtr<u64, HighByteOther, u64> = StuffedPtr::from_tagged(tagged);
+        assert_eq!(back, stuffed);
+        unsafe { *back.unwrap_ptr() += 1 };
+        assert_eq!(a, 2);
+
+        let stuffed: StuffedPtr<u64, HighByteOther, u64> = StuffedPtr::new_other(12);
+        let tagged: TaggedPtr<u64, HighByte, u64> = TaggedPtr::from_stuffed(stuffed);
+        assert_eq!(tagged.get_tag(), 12);
+        let back: StuffedPtr<u64, HighByteOther, u64> = StuffedPtr::from_tagged(tagged);
+        assert_eq!(back, stuffed);
+    }
+
+    #[test]
+    fn from_tagged_discards_ptr() {
+        let mut a = 1_u64;
+        let tagged: TaggedPtr<u64, HighByte, u64> = TaggedPtr::new(&mut a, 3);
+        let stuffed: StuffedPtr<u64, HighByteOther, u64> = StuffedPtr::from_tagged(tagged);
+        assert_eq!(stuffed.other(), Some(3));
+    }
+
+    #[test]
+    fn eq_hash() {
+        let mut a = 1_u64;
+        let t1: TaggedPtr<u64, BoolTag> = TaggedPtr::new(&mut a, true);
+        let t2 = TaggedPtr::new(&mut a, true);
+        let t3 = t1.with_tag(false);
+
+        assert_eq!(t1, t2);
+        assert_ne!(t1, t3);
+
+        let set: HashSet<_> = [t1, t2, t3].iter().copied().collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn debug() {
+        let mut a = 1_u64;
+        let tagged: TaggedPtr<u64, BoolTag> = TaggedPtr::new(&mut a, true);
+        let expected = format!("TaggedPtr {{ ptr: {:?}, tag: true }}", &mut a as *mut u64);
+        assert_eq!(format!("{:?}", tagged), expected);
+    }
+}
@@ -0,0 +1,95 @@
+use core::{marker::PhantomData, mem};
+
+use crate::TaggingStrategy;
+
+/// A tagging strategy that stores a tag in the `BITS` low bits of the address, which are always
+/// zero for pointers that are aligned to at least `2^BITS` bytes. For example, `AlignTag<3>` can
+/// be used for pointers to 8-byte aligned types.
+///
+/// Only available with the `tagged` and `const-generics` features.
+///
+/// Using it with a pointee type that isn't aligned to `2^BITS` is a compile error. It's checked
+/// with debug assertions that the address doesn't have any of the low bits set and that the tag
+/// fits into them.
+///
+/// ```
+/// use stuff::{AlignTag, TaggedPtr};
+///
+/// let mut a = 1_u32;
+/// let tagged: TaggedPtr<u32, AlignTag<2>> = TaggedPtr::new(&mut a, 3);
+///
+/// assert_eq!(tagged.get_ptr(), &mut a as *mut u32);
+/// assert_eq!(tagged.get_tag(), 3);
+/// ```
+pub struct AlignTag<const BITS: usize>;
+
+impl<const BITS: usize> AlignTag<BITS> {
+    const MASK: usize = (1 << BITS) - 1;
+}
+
+struct AssertTagFits<T, const BITS: usize>(PhantomData<T>);
+
+impl<T, const BITS: usize> AssertTagFits<T, BITS> {
+    const OK: () = assert!(
+        BITS <= mem::align_of::<T>().trailing_zeros() as usize,
+        "The alignment of the pointee is too small for the tag bits"
+    );
+}
+
+impl<const BITS: usize> TaggingStrategy<usize> for AlignTag<BITS> {
+    type Tag = usize;
+
+    fn get_tag(data: usize) -> Self::Tag {
+        data & Self::MASK
+    }
+
+    fn get_ptr_addr(data: usize) -> usize {
+        data & !Self::MASK
+    }
+
+    fn set(addr: usize, tag: Self::Tag) -> usize {
+        debug_assert_eq!(addr & Self::MASK, 0, "Pointer not aligned enough");
+        debug_assert!(tag <= Self::MASK, "Tag too big for the low bits");
+        addr | tag
+    }
+
+    fn assert_fits<T>() {
+        let () = AssertTagFits::<T, BITS>::OK;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::undocumented_unsafe_blocks)]
+
+    use std::boxed::Box;
+
+    use super::AlignTag;
+    use crate::TaggedPtr;
+
+    #[repr(align(8))]
+    struct Aligned(u64);
+
+    #[test]
+    fn tags() {
+        let ptr = Box::into_raw(Box::new(Aligned(5)));
+
+        for tag in 0..8 {
+            let tagged: TaggedPtr<Aligned, AlignTag<3>> = TaggedPtr::new(ptr, tag);
+            assert_eq!(tagged.get_tag(), tag);
+            assert_eq!(tagged.get_ptr(), ptr);
+        }
+
+        let tagged: TaggedPtr<Aligned, AlignTag<3>> = TaggedPtr::new(ptr, 7);
+        let boxed = unsafe { Box::from_raw(tagged.get_ptr()) };
+        assert_eq!(boxed.0, 5);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Tag too big for the low bits")]
+    fn tag_too_big() {
+        let mut a = Aligned(5);
+        let _: TaggedPtr<Aligned, AlignTag<3>> = TaggedPtr::new(&mut a, 8);
+    }
+}
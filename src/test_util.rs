@@ -0,0 +1,126 @@
+//! Helpers for testing custom [`Backend`] and [`StuffingStrategy`] implementations.
+//!
+//! Only available with the `test-util` feature.
+
+use core::fmt::Debug;
+
+use sptr::Strict;
+
+use crate::{Backend, StuffedPtr, StuffingStrategy, Unstuffed};
+
+/// Assert that the backend `B` fulfills the contract of [`Backend`] for every address in `addrs`.
+///
+/// Every address is set together with the provenance of a real pointer. It then checks that
+/// [`Backend::get_ptr`] returns the same address, that [`Backend::get_int`] agrees with it, and
+/// that the returned provenance can still be used to read from the original pointer. Run it
+/// under Miri to also catch lost provenance.
+///
+/// ```
+/// use stuff::test_util::assert_backend_roundtrip;
+///
+/// assert_backend_roundtrip::<u64>(&[0, 1, 0xdead_beef, u64::max_value()]);
+/// ```
+///
+/// # Panics
+/// Panics if the backend violates the contract for any of the addresses.
+pub fn assert_backend_roundtrip<B>(addrs: &[B])
+where
+    B: Backend + Copy + PartialEq + Debug,
+{
+    let mut value = 0x5a_u8;
+    let provenance = &mut value as *mut u8;
+
+    for &addr in addrs {
+        let stored = B::set_ptr(provenance as *mut (), addr);
+
+        let (new_provenance, new_addr) = B::get_ptr(stored);
+        assert_eq!(new_addr, addr, "`get_ptr` returned a different address");
+        assert_eq!(
+            B::get_int(stored),
+            addr,
+            "`get_int` returned a different address"
+        );
+
+        let ptr = Strict::with_addr(new_provenance as *mut u8, Strict::addr(provenance));
+        // SAFETY: if the backend keeps provenance as it must, this points to `value`
+        let read = unsafe { *ptr };
+        assert_eq!(read, 0x5a, "the provenance of the pointer was not kept");
+    }
+}
+
+/// Assert that the strategy `S` round trips all of the `others` and pointer addresses in `addrs`
+/// through the backend `B`.
+///
+/// For every `other`, [`StuffingStrategy::extract`] of [`StuffingStrategy::stuff_other`] must
+/// return the same `other`. For every address, `extract` of [`StuffingStrategy::stuff_ptr`] must
+/// return the same address. [`StuffingStrategy::is_other`] must agree with `extract` for all of
+/// them. See [`assert_strategy_sound!`](crate::assert_strategy_sound) for a shorthand with some
+/// default addresses.
+///
+/// Additionally, a pointer to a local value that is aligned to 16 bytes is stuffed into a
+/// [`StuffedPtr`], and the value is read through the pointer recovered from it. Run it under Miri
+/// to also catch lost provenance.
+///
+/// # Panics
+/// Panics if any of the values doesn't round trip.
+pub fn assert_strategy_roundtrip<S, B>(others: &[S::Other], addrs: &[usize])
+where
+    S: StuffingStrategy<B>,
+    S::Other: PartialEq + Debug,
+    B: Backend,
+{
+    for &other in others {
+        let extracted = S::extract(S::stuff_other(other));
+        assert_eq!(
+            extracted,
+            Unstuffed::Other(other),
+            "`other` data didn't round trip"
+        );
+        assert!(
+            S::is_other(S::stuff_other(other)),
+            "`is_other` disagrees with `extract` for `other` data"
+        );
+    }
+
+    for &addr in addrs {
+        let extracted = S::extract(S::stuff_ptr(addr));
+        assert_eq!(extracted, Unstuffed::Ptr(addr), "pointer didn't round trip");
+        assert!(
+            !S::is_other(S::stuff_ptr(addr)),
+            "`is_other` disagrees with `extract` for a pointer"
+        );
+    }
+
+    #[repr(align(16))]
+    struct Aligned(u8);
+
+    let mut value = Aligned(0x5a);
+    let stuffed: StuffedPtr<Aligned, S, B> = StuffedPtr::new_ptr(&mut value);
+    let ptr = stuffed.ptr().expect("pointer didn't round trip");
+    // SAFETY: if the strategy and the backend keep the pointer as they must, this points to `value`
+    let read = unsafe { (*ptr).0 };
+    assert_eq!(read, 0x5a, "the provenance of the pointer was not kept");
+}
+
+/// Assert that a strategy round trips the given `other` values and some pointer addresses through
+/// a backend, using [`assert_strategy_roundtrip`](crate::test_util::assert_strategy_roundtrip).
+///
+/// The addresses are all aligned to 16 bytes and fit into 32 bits, so they should be accepted by
+/// every strategy.
+///
+/// Only available with the `test-util` feature.
+///
+/// ```
+/// use stuff::{assert_strategy_sound, strategies::NanBoxStrategy};
+///
+/// assert_strategy_sound!(NanBoxStrategy, u64, [0.0, -1.5, f64::INFINITY]);
+/// ```
+#[macro_export]
+macro_rules! assert_strategy_sound {
+    ($strategy:ty, $backend:ty, [$($other:expr),* $(,)?]) => {
+        $crate::test_util::assert_strategy_roundtrip::<$strategy, $backend>(
+            &[$($other),*],
+            &[0x10, 0x100, 0x1000, 0xdead_bef0, 0x7fff_fff0],
+        )
+    };
+}
@@ -0,0 +1,337 @@
+#![warn(rust_2018_idioms)]
+#![warn(missing_docs)]
+
+//! Derive macros for [`stuff`](https://docs.rs/stuff).
+//!
+//! Use them through the `derive` feature of `stuff` instead of depending on this crate directly.
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Ident, Type};
+
+/// Derive `StuffingStrategy<u64>` for an enum, using the enum itself as the `other` data.
+///
+/// The encoding scheme is chosen with an attribute on the enum. Currently, only `#[stuff(qnan)]`
+/// is supported, which NaN-boxes the values:
+///
+/// - At most one variant may contain a single `f64`, which is stored as a float. NaN values are
+///   replaced with a canonical NaN.
+/// - All other variants must be unit variants or contain a single `bool`, `u8`, `u16`, `u32`,
+///   `i8`, `i16` or `i32`. They are stored in the payload of a quiet NaN, with the variant index
+///   as a tag.
+/// - Pointers are stored in the payload of a quiet NaN with the sign bit set. Their addresses must
+///   fit into the lower 50 bits.
+///
+/// The enum must be `Copy`.
+///
+/// ```
+/// use stuff::{StuffedPtr, StuffingStrategy};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, StuffingStrategy)]
+/// #[stuff(qnan)]
+/// enum Value {
+///     Float(f64),
+///     Int(i32),
+///     Nil,
+/// }
+///
+/// let int: StuffedPtr<(), Value, u64> = StuffedPtr::new_other(Value::Int(-1));
+/// assert_eq!(int.other(), Some(Value::Int(-1)));
+/// ```
+#[proc_macro_derive(StuffingStrategy, attributes(stuff))]
+pub fn derive_stuffing_strategy(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derive `TaggingStrategy<usize>` for a C-like enum, using the enum itself as the tag.
+///
+/// The index of the variant is stored in the lowest bits of the address, using as few bits as
+/// possible (one bit for two variants, two bits for up to four variants and so on). The pointee
+/// must have an alignment big enough to leave these bits free, which is checked with a debug
+/// assertion. Requires the `tagged` feature of `stuff` as well.
+///
+/// All variants must be unit variants, and the enum must be `Copy`.
+///
+/// ```
+/// use stuff::{TagBits, TaggedPtr};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, TagBits)]
+/// enum Color {
+///     Red,
+///     Black,
+/// }
+///
+/// let mut a = 1_u64;
+/// let tagged: TaggedPtr<u64, Color> = TaggedPtr::new(&mut a, Color::Black);
+/// assert_eq!(tagged.get_tag(), Color::Black);
+/// assert_eq!(tagged.get_ptr(), &mut a as *mut u64);
+/// ```
+#[proc_macro_derive(TagBits)]
+pub fn derive_tag_bits(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_tag_bits(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+const QNAN: u64 = 0x7ffc000000000000;
+const SIGN_BIT: u64 = 0x8000000000000000;
+
+enum Payload {
+    Unit,
+    Float,
+    Int(Ident),
+    Bool,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    parse_scheme(&input)?;
+
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(Error::new(
+                Span::call_site(),
+                "`StuffingStrategy` can only be derived for enums",
+            ))
+        }
+    };
+
+    let mut float_variant = None;
+    let mut stuff_arms = Vec::new();
+    let mut extract_arms = Vec::new();
+
+    for (index, variant) in data.variants.iter().enumerate() {
+        let name = &variant.ident;
+        let tag = index as u64;
+
+        match parse_payload(&variant.fields)? {
+            Payload::Unit => {
+                stuff_arms.push(quote! {
+                    Self::#name => #QNAN | (#tag << 32),
+                });
+                extract_arms.push(quote! {
+                    #tag => Self::#name,
+                });
+            }
+            Payload::Float => {
+                if float_variant.is_some() {
+                    return Err(Error::new_spanned(
+                        variant,
+                        "only one variant may contain an `f64`",
+                    ));
+                }
+                float_variant = Some(name);
+                stuff_arms.push(quote! {
+                    Self::#name(float) => {
+                        if float.is_nan() {
+                            ::core::f64::NAN.to_bits()
+                        } else {
+                            float.to_bits()
+                        }
+                    }
+                });
+            }
+            Payload::Int(ty) => {
+                stuff_arms.push(quote! {
+                    Self::#name(int) => #QNAN | (#tag << 32) | u64::from(int as u32),
+                });
+                extract_arms.push(quote! {
+                    #tag => Self::#name(payload as #ty),
+                });
+            }
+            Payload::Bool => {
+                stuff_arms.push(quote! {
+                    Self::#name(boolean) => #QNAN | (#tag << 32) | u64::from(boolean),
+                });
+                extract_arms.push(quote! {
+                    #tag => Self::#name(payload != 0),
+                });
+            }
+        }
+    }
+
+    let extract_float = float_variant.map(|name| {
+        quote! {
+            if data & #QNAN != #QNAN {
+                return ::stuff::Unstuffed::Other(Self::#name(f64::from_bits(data)));
+            }
+        }
+    });
+
+    // floats are never pointers, even if their sign bit is set
+    let is_float = float_variant.map(|_| {
+        quote! {
+            data & #QNAN != #QNAN ||
+        }
+    });
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::stuff::StuffingStrategy<u64> for #name #ty_generics #where_clause {
+            type Other = Self;
+
+            fn stuff_other(inner: Self::Other) -> u64 {
+                match inner {
+                    #(#stuff_arms)*
+                }
+            }
+
+            fn extract(data: u64) -> ::stuff::Unstuffed<usize, Self::Other> {
+                #extract_float
+                if data & #SIGN_BIT != 0 {
+                    return ::stuff::Unstuffed::Ptr((data & !(#SIGN_BIT | #QNAN)) as usize);
+                }
+                let payload = data as u32;
+                ::stuff::Unstuffed::Other(match (data >> 32) & 0xffff {
+                    #(#extract_arms)*
+                    _ => unreachable!("invalid tag"),
+                })
+            }
+
+            fn stuff_ptr(addr: usize) -> u64 {
+                #SIGN_BIT | #QNAN | addr as u64
+            }
+
+            fn is_other(data: u64) -> bool {
+                #is_float data & #SIGN_BIT == 0
+            }
+        }
+    })
+}
+
+fn expand_tag_bits(input: DeriveInput) -> syn::Result<TokenStream> {
+    let data = match &input.data {
+        Data::Enum(data) if !data.variants.is_empty() => data,
+        _ => {
+            return Err(Error::new(
+                Span::call_site(),
+                "`TagBits` can only be derived for enums with at least one variant",
+            ))
+        }
+    };
+
+    let mut set_arms = Vec::new();
+    let mut get_arms = Vec::new();
+
+    for (index, variant) in data.variants.iter().enumerate() {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                &variant.fields,
+                "`TagBits` can only be derived for enums with unit variants",
+            ));
+        }
+        let name = &variant.ident;
+        set_arms.push(quote! {
+            Self::#name => #index,
+        });
+        get_arms.push(quote! {
+            #index => Self::#name,
+        });
+    }
+
+    // the number of bits needed to store the highest variant index
+    let bits = usize::BITS - (data.variants.len() - 1).leading_zeros();
+    let mask = (1_usize << bits) - 1;
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::stuff::TaggingStrategy<usize> for #name #ty_generics #where_clause {
+            type Tag = Self;
+
+            fn get_tag(data: usize) -> Self::Tag {
+                match data & #mask {
+                    #(#get_arms)*
+                    _ => unreachable!("invalid tag"),
+                }
+            }
+
+            fn get_ptr_addr(data: usize) -> usize {
+                data & !#mask
+            }
+
+            fn set(addr: usize, tag: Self::Tag) -> usize {
+                debug_assert_eq!(addr & #mask, 0, "Pointer not aligned enough");
+                let tag: usize = match tag {
+                    #(#set_arms)*
+                };
+                addr | tag
+            }
+
+            fn assert_fits<T>() {
+                debug_assert!(
+                    ::core::mem::align_of::<T>() > #mask,
+                    "The alignment of the pointee is too small for the tag bits",
+                );
+            }
+        }
+    })
+}
+
+fn parse_scheme(input: &DeriveInput) -> syn::Result<()> {
+    let mut qnan = false;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("stuff"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("qnan") {
+                qnan = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported scheme, expected `qnan`"))
+            }
+        })?;
+    }
+
+    if qnan {
+        Ok(())
+    } else {
+        Err(Error::new(
+            Span::call_site(),
+            "missing scheme, add `#[stuff(qnan)]`",
+        ))
+    }
+}
+
+fn parse_payload(fields: &Fields) -> syn::Result<Payload> {
+    let field = match fields {
+        Fields::Unit => return Ok(Payload::Unit),
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0],
+        _ => {
+            return Err(Error::new_spanned(
+                fields,
+                "variants must be unit variants or contain a single unnamed field",
+            ))
+        }
+    };
+
+    let ident = match &field.ty {
+        Type::Path(path) if path.qself.is_none() => path.path.get_ident(),
+        _ => None,
+    };
+
+    match ident {
+        Some(ident) if ident == "f64" => Ok(Payload::Float),
+        Some(ident) if ident == "bool" => Ok(Payload::Bool),
+        Some(ident)
+            if ["u8", "u16", "u32", "i8", "i16", "i32"]
+                .iter()
+                .any(|int| ident == int) =>
+        {
+            Ok(Payload::Int(ident.clone()))
+        }
+        _ => Err(Error::new_spanned(
+            &field.ty,
+            "unsupported field type, expected `f64`, `bool` or an integer up to 32 bits",
+        )),
+    }
+}
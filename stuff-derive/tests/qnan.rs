@@ -0,0 +1,77 @@
+#![allow(clippy::undocumented_unsafe_blocks)]
+
+use stuff::{StuffedPtr, StuffingStrategy};
+
+#[derive(Debug, Clone, Copy, PartialEq, StuffingStrategy)]
+#[stuff(qnan)]
+enum Value {
+    Float(f64),
+    Int(i32),
+    Byte(u8),
+    Bool(bool),
+    Nil,
+}
+
+type Stuffed = StuffedPtr<u64, Value, u64>;
+
+#[test]
+fn others() {
+    for &value in &[
+        Value::Float(0.0),
+        Value::Float(-1.5),
+        Value::Float(f64::INFINITY),
+        Value::Int(i32::MIN),
+        Value::Int(i32::MAX),
+        Value::Int(-1),
+        Value::Byte(u8::MAX),
+        Value::Bool(false),
+        Value::Bool(true),
+        Value::Nil,
+    ] {
+        let stuffed: Stuffed = StuffedPtr::new_other(value);
+        assert_eq!(stuffed.other(), Some(value));
+        assert!(stuffed.ptr().is_none());
+    }
+}
+
+#[test]
+fn nan() {
+    let stuffed: Stuffed =
+        StuffedPtr::new_other(Value::Float(f64::from_bits(0x7ffc000000000000 | 1)));
+    match stuffed.other() {
+        Some(Value::Float(float)) => assert!(float.is_nan()),
+        other => panic!("expected a float, got {:?}", other),
+    }
+}
+
+#[test]
+fn pointer() {
+    let stuffed: Stuffed = StuffedPtr::new_ptr(Box::into_raw(Box::new(5)));
+    assert!(stuffed.other().is_none());
+
+    let boxed = unsafe { Box::from_raw(stuffed.ptr().unwrap()) };
+    assert_eq!(*boxed, 5);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, StuffingStrategy)]
+#[stuff(qnan)]
+enum NoFloat {
+    Int(i32),
+    Nil,
+}
+
+#[test]
+fn is_other() {
+    stuff::assert_strategy_sound!(
+        Value,
+        u64,
+        [
+            Value::Float(-1.5),
+            Value::Float(f64::NEG_INFINITY),
+            Value::Int(-1),
+            Value::Bool(true),
+            Value::Nil,
+        ]
+    );
+    stuff::assert_strategy_sound!(NoFloat, u64, [NoFloat::Int(-1), NoFloat::Nil]);
+}
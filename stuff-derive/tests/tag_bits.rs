@@ -0,0 +1,62 @@
+#![allow(clippy::undocumented_unsafe_blocks)]
+
+use stuff::{TagBits, TaggedPtr, TaggingStrategy};
+
+#[derive(Debug, Clone, Copy, PartialEq, TagBits)]
+enum Color {
+    Red,
+    Black,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, TagBits)]
+enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+#[test]
+fn two_variants() {
+    assert_eq!(Color::set(0, Color::Black), 1);
+    assert_eq!(Color::get_ptr_addr(0b11), 0b10);
+
+    let mut a = 1_u16;
+    for &color in &[Color::Red, Color::Black] {
+        let tagged: TaggedPtr<u16, Color> = TaggedPtr::new(&mut a, color);
+        assert_eq!(tagged.get_tag(), color);
+        unsafe { *tagged.get_ptr() += 1 };
+    }
+    assert_eq!(a, 3);
+}
+
+#[test]
+fn four_variants() {
+    assert_eq!(Direction::set(0, Direction::West), 3);
+    assert_eq!(Direction::get_ptr_addr(0b111), 0b100);
+
+    let mut a = 1_u32;
+    for &direction in &[
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ] {
+        let tagged: TaggedPtr<u32, Direction> = TaggedPtr::new(&mut a, direction);
+        assert_eq!(tagged.get_tag(), direction);
+        assert_eq!(
+            tagged.with_tag(Direction::North).get_tag(),
+            Direction::North
+        );
+        unsafe { *tagged.get_ptr() += 1 };
+    }
+    assert_eq!(a, 5);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "The alignment of the pointee is too small for the tag bits")]
+fn alignment_too_small() {
+    let mut a = 1_u16;
+    let _: TaggedPtr<u16, Direction> = TaggedPtr::new(&mut a, Direction::North);
+}
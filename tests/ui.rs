@@ -0,0 +1,10 @@
+#![cfg(all(feature = "tagged", feature = "const-generics", not(miri)))]
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    // the pass cases make trybuild run `cargo build` instead of `cargo check`, which is needed for
+    // the post-monomorphization errors of the fail cases
+    t.pass("tests/ui/pass/*.rs");
+    t.compile_fail("tests/ui/fail/*.rs");
+}
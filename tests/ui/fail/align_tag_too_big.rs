@@ -0,0 +1,6 @@
+use stuff::{AlignTag, TaggedPtr};
+
+fn main() {
+    let mut a = 1_u16;
+    let _: TaggedPtr<u16, AlignTag<2>> = TaggedPtr::new(&mut a, 1);
+}
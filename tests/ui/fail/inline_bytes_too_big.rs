@@ -0,0 +1,5 @@
+use stuff::{strategies::InlineBytes, StuffedPtr};
+
+fn main() {
+    let _: StuffedPtr<Vec<u8>, InlineBytes<16>, u128> = StuffedPtr::new_other([0; 16]);
+}
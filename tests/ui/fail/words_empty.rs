@@ -0,0 +1,6 @@
+use stuff::{Backend, Words};
+
+fn main() {
+    let mut a = 1_u8;
+    let _ = Words::set_ptr(&mut a as *mut u8 as *mut (), Words([]));
+}
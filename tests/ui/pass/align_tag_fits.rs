@@ -0,0 +1,7 @@
+use stuff::{AlignTag, TaggedPtr};
+
+fn main() {
+    let mut a = 1_u32;
+    let tagged: TaggedPtr<u32, AlignTag<2>> = TaggedPtr::new(&mut a, 3);
+    assert_eq!(tagged.get_tag(), 3);
+}